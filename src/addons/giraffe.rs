@@ -1,7 +1,11 @@
-use crate::bike::{control_player, Bicycle, BicycleControl, Player, GAME_BICYCLE_LENGTH};
+use crate::bike::{
+    control_player, Bicycle, BicycleControl, DriftTurbo, Player, GAME_BICYCLE_LENGTH,
+};
+use crate::arena::ItemHitEvent;
 use crate::bike_config::ForBicycle;
 use crate::game_state::{DespawnMe, RaceState};
-use crate::waypoint::{follow_waypoint, WaypointAi};
+use crate::team::Team;
+use crate::waypoint::follow_waypoint;
 use avian2d::prelude::{Collider, Collision, LinearVelocity, RigidBody};
 use bevy::prelude::*;
 
@@ -12,8 +16,7 @@ impl Plugin for GiraffePlugin {
         app.add_systems(
             Update,
             (
-                (giraffe_player_control_system, giraffe_ai_control_system)
-                    .run_if(in_state(RaceState::Playing)),
+                giraffe_player_control_system.run_if(in_state(RaceState::Playing)),
                 poo_collision.after(control_player).after(follow_waypoint),
                 poo_collision_update,
             ),
@@ -23,21 +26,20 @@ impl Plugin for GiraffePlugin {
 
 #[derive(Debug, Component)]
 pub struct Giraffe {
-    ai_timer: Timer,
-    poo_left: usize,
+    pub poo_left: usize,
 }
 
 impl Default for Giraffe {
     fn default() -> Self {
-        Self {
-            ai_timer: Timer::from_seconds(3.0, TimerMode::Repeating),
-            poo_left: 3,
-        }
+        Self { poo_left: 3 }
     }
 }
 
 #[derive(Debug, Component)]
-pub struct Poo;
+pub struct Poo {
+    /// The team of the racer that dropped it, teammates ride over it
+    team: Option<Team>,
+}
 
 pub fn giraffe_player_control_system(
     mut commands: Commands,
@@ -45,44 +47,16 @@ pub fn giraffe_player_control_system(
         (Entity, &mut Giraffe, &GlobalTransform, &ForBicycle, &Parent),
         Without<Player>,
     >,
-    player_query: Query<(&Player, &GlobalTransform)>,
+    player_query: Query<(&Player, &GlobalTransform, Option<&Team>)>,
     input: Res<ButtonInput<KeyCode>>,
     assets: Res<AssetServer>,
 ) {
     for (entity, mut giraffe, transform, for_bicycle, parent) in query.iter_mut() {
-        if let Ok((player, parent_transform)) = player_query.get(for_bicycle.0) {
+        if let Ok((player, parent_transform, team)) = player_query.get(for_bicycle.0) {
             if input.just_pressed(KeyCode::Space) {
                 if giraffe.poo_left > 0 {
                     giraffe.poo_left -= 1;
-                    spawn_poo(&mut commands, parent_transform, &assets);
-                }
-                if giraffe.poo_left == 0 {
-                    commands.entity(parent.get()).remove_children(&[entity]);
-                    commands.entity(entity).despawn_recursive();
-                }
-            }
-        }
-    }
-}
-
-pub fn giraffe_ai_control_system(
-    mut commands: Commands,
-    mut query: Query<
-        (Entity, &mut Giraffe, &GlobalTransform, &ForBicycle, &Parent),
-        Without<WaypointAi>,
-    >,
-    player_query: Query<(&WaypointAi, &GlobalTransform)>,
-    assets: Res<AssetServer>,
-    time: Res<Time>,
-) {
-    for (entity, mut giraffe, transform, for_bicycle, parent) in query.iter_mut() {
-        giraffe.ai_timer.tick(time.delta());
-
-        if giraffe.ai_timer.finished() {
-            if let Ok((player, parent_transform)) = player_query.get(for_bicycle.0) {
-                if giraffe.poo_left > 0 {
-                    giraffe.poo_left -= 1;
-                    spawn_poo(&mut commands, parent_transform, &assets);
+                    spawn_poo(&mut commands, parent_transform, team.copied(), &assets);
                 }
                 if giraffe.poo_left == 0 {
                     commands.entity(parent.get()).remove_children(&[entity]);
@@ -96,6 +70,7 @@ pub fn giraffe_ai_control_system(
 pub fn spawn_poo(
     commands: &mut Commands,
     bike_transform: &GlobalTransform,
+    team: Option<Team>,
     assets: &Res<AssetServer>,
 ) {
     let bike_dir = bike_transform.up().normalize();
@@ -122,7 +97,7 @@ pub fn spawn_poo(
             ..Default::default()
         },
         Collider::circle(0.2),
-        Poo,
+        Poo { team },
     ));
 }
 
@@ -131,22 +106,49 @@ pub struct PooCollision {
     pub timer: Timer,
 }
 
+/// Stops a bike that ran into a poo, a drift it was in is lost without a boost
+fn slip(
+    control: &mut BicycleControl,
+    velocity: &mut LinearVelocity,
+    turbo: Option<&mut DriftTurbo>,
+) {
+    velocity.0 = Vec2::ZERO;
+    control.turn = 0.0;
+    control.acceleration = 0.0;
+    control.drift = false;
+    if let Some(turbo) = turbo {
+        turbo.cancel();
+    }
+}
+
 pub fn poo_collision(
     mut commands: Commands,
     mut events: EventReader<Collision>,
     poo_query: Query<(Entity, &Poo)>,
-    mut bicycle_query: Query<(Entity, &Bicycle, &mut BicycleControl, &mut LinearVelocity)>,
+    mut bicycle_query: Query<(
+        Entity,
+        &Bicycle,
+        &mut BicycleControl,
+        &mut LinearVelocity,
+        Option<&Team>,
+        Option<&mut DriftTurbo>,
+    )>,
+    mut hit_events: EventWriter<ItemHitEvent>,
     assets: Res<AssetServer>,
 ) {
     for Collision(contacts) in events.read() {
-        if let Some((poo, ..)) = poo_query
+        if let Some((poo_entity, poo)) = poo_query
             .iter_many([contacts.entity1, contacts.entity2])
             .next()
         {
-            if let Some((bicycle, _, mut control, mut velocity)) = bicycle_query
+            if let Some((bicycle, _, mut control, mut velocity, team, mut turbo)) = bicycle_query
                 .iter_many_mut([contacts.entity1, contacts.entity2])
                 .fetch_next()
             {
+                if Team::same(poo.team.as_ref(), team) {
+                    continue;
+                }
+
                 commands.entity(bicycle).insert(PooCollision {
                     timer: Timer::from_seconds(0.5, TimerMode::Once),
                 });
@@ -159,11 +161,11 @@ pub fn poo_collision(
                     }
                 ));
 
-                velocity.0 = Vec2::ZERO;
-                control.turn = 0.0;
-                control.acceleration = 0.0;
+                slip(&mut control, &mut velocity, turbo.as_deref_mut());
+
+                hit_events.send(ItemHitEvent { target: bicycle });
 
-                commands.entity(poo).despawn();
+                commands.entity(poo_entity).despawn();
             }
         }
     }
@@ -191,3 +193,56 @@ pub fn poo_collision_update(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bike::{drift_factor_system, BicycleParams};
+    use std::time::Duration;
+
+    fn step(app: &mut App) {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(1.0 / 60.0));
+        app.update();
+    }
+
+    #[test]
+    fn poo_hit_mid_drift_gives_no_boost() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_systems(Update, drift_factor_system);
+        let bike = app
+            .world_mut()
+            .spawn((
+                BicycleControl {
+                    turn: 1.0,
+                    acceleration: 1.0,
+                    drift: true,
+                },
+                BicycleParams::default(),
+                DriftTurbo::default(),
+                LinearVelocity::ZERO,
+                Transform::default(),
+            ))
+            .id();
+
+        // Slide sideways long enough to charge a boost
+        for _ in 0..30 {
+            app.world_mut().get_mut::<LinearVelocity>(bike).unwrap().0 = Vec2::new(4.0, 4.0);
+            step(&mut app);
+        }
+        assert!(app.world().get::<DriftTurbo>(bike).unwrap().tier() > 0);
+
+        let world = app.world_mut();
+        let mut query =
+            world.query::<(&mut BicycleControl, &mut LinearVelocity, &mut DriftTurbo)>();
+        let (mut control, mut velocity, mut turbo) = query.single_mut(world);
+        slip(&mut control, &mut velocity, Some(&mut *turbo));
+
+        step(&mut app);
+        let turbo = app.world().get::<DriftTurbo>(bike).unwrap();
+        assert_eq!(turbo.charge, 0.0);
+        assert!(!turbo.boosting());
+    }
+}
@@ -1,10 +1,12 @@
 use crate::addons::lasso::{MoveToTarget, MovedToTargetEvent};
 use crate::bike::{Bicycle, Player};
 use crate::bike_config::ForBicycle;
+use crate::arena::{closest_opponent, ItemHitEvent};
+use crate::elimination::Eliminated;
 use crate::ranking::{Progress, Rank};
+use crate::team::Team;
 use bevy::prelude::*;
-use rand::random;
-use crate::game_state::{DespawnMe, RaceState};
+use crate::game_state::{race_running, DespawnMe, RaceConfig, RaceState, RaceType};
 
 pub struct HookPlugin;
 
@@ -13,28 +15,17 @@ impl Plugin for HookPlugin {
         app.add_systems(
             Update,
             (
-                (player_hook_control_system,
-                fire_hook_system).run_if(in_state(RaceState::Playing)),
+                player_hook_control_system.run_if(in_state(RaceState::Playing)),
+                fire_hook_system.run_if(race_running),
                 hook_hit_system,
-                ai_hook_control_system,
             ),
         )
         .add_event::<FireHookEvent>();
     }
 }
 
-#[derive(Debug, Component)]
-pub struct HookAddon {
-    ai_timer: Timer,
-}
-
-impl Default for HookAddon {
-    fn default() -> Self {
-        Self {
-            ai_timer: Timer::from_seconds(random::<f32>() * 5.0, TimerMode::Once),
-        }
-    }
-}
+#[derive(Debug, Component, Default)]
+pub struct HookAddon;
 
 pub const HOOK_SPEED: f32 = 0.1;
 
@@ -46,7 +37,7 @@ pub struct Hook {
 
 #[derive(Debug, Event)]
 pub struct FireHookEvent {
-    by: Entity,
+    pub by: Entity,
 }
 
 #[derive(Debug, Component)]
@@ -71,51 +62,40 @@ pub fn player_hook_control_system(
     }
 }
 
-pub fn ai_hook_control_system(
-    mut commands: Commands,
-    mut query: Query<(
-        Entity,
-        &mut HookAddon,
-        &GlobalTransform,
-        &ForBicycle,
-        &Parent,
-    )>,
-    mut is_not_player_query: Query<&Bicycle, Without<Player>>,
-    mut events: EventWriter<FireHookEvent>,
-    time: Res<Time>,
-) {
-    for (entity, mut hook, transform, for_bicycle, parent) in query.iter_mut() {
-        if let Ok(_) = is_not_player_query.get(for_bicycle.0) {
-            if hook.ai_timer.tick(time.delta()).just_finished() {
-                events.send(FireHookEvent { by: for_bicycle.0 });
-
-                commands.entity(parent.get()).remove_children(&[entity]);
-                commands.entity(entity).despawn_recursive();
-            }
-        }
-    }
-}
-
 pub fn fire_hook_system(
     mut commands: Commands,
-    mut by_query: Query<(Entity, &Rank, &GlobalTransform)>,
-    mut target_query: Query<(Entity, &Rank, &GlobalTransform)>,
+    mut by_query: Query<(Entity, &Rank, &GlobalTransform, Option<&Team>)>,
+    mut target_query: Query<
+        (Entity, &Rank, &GlobalTransform, Option<&Team>),
+        Without<Eliminated>,
+    >,
     mut events: EventReader<FireHookEvent>,
+    race_config: Res<RaceConfig>,
     assets: Res<AssetServer>,
 ) {
     for (event) in events.read() {
-        let (by, by_rank, by_transform) = by_query.get_mut(event.by).unwrap();
-
-        let mut target = None;
-
-        for (entity, rank, transform) in target_query.iter_mut() {
-            if rank.0 + 1 == by_rank.0 {
-                target = Some((entity, transform.translation()));
-                break;
-            }
-        }
-
-        if let Some((target_entity, target_position)) = target {
+        let Ok((by, by_rank, by_transform, by_team)) = by_query.get_mut(event.by) else {
+            continue;
+        };
+
+        let opponents = target_query
+            .iter()
+            .filter(|(.., team)| !Team::same(by_team, *team));
+        let target = if race_config.race_type == RaceType::Arena {
+            closest_opponent(
+                by,
+                by_transform,
+                opponents.map(|(entity, _, transform, _)| (entity, transform)),
+            )
+        } else {
+            // The closest racer ahead, teammates are skipped
+            opponents
+                .filter(|(_, rank, ..)| rank.0 < by_rank.0)
+                .max_by_key(|(_, rank, ..)| rank.0)
+                .map(|(entity, ..)| entity)
+        };
+
+        if let Some(target_entity) = target {
             commands.spawn((
                 DespawnMe,
                 Hook {
@@ -146,6 +126,8 @@ pub fn hook_hit_system(
     hook_query: Query<(&Hook)>,
     moving_back_query: Query<(&HookCaughtAndMovingBack)>,
     mut progress_query: Query<(&mut Progress)>,
+    mut hit_events: EventWriter<ItemHitEvent>,
+    race_config: Res<RaceConfig>,
     assets: Res<AssetServer>,
 ) {
     for event in events.read() {
@@ -153,6 +135,12 @@ pub fn hook_hit_system(
             println!("Hook hit target");
             commands.entity(event.entity).despawn_recursive();
 
+            // There is no progress to take in a battle, the hook pops a balloon instead
+            if race_config.race_type == RaceType::Arena {
+                hit_events.send(ItemHitEvent { target: hook.target });
+                continue;
+            }
+
             commands.entity(hook.by).insert((
                 MoveToTarget {
                     target: hook.target,
@@ -175,9 +163,12 @@ pub fn hook_hit_system(
                 .entity(event.entity)
                 .remove::<(HookCaughtAndMovingBack, MoveToTarget)>();
 
-            let target_progress = progress_query.get_mut(event.target).unwrap().clone();
-            let mut my_progress = progress_query.get_mut(event.entity).unwrap();
-            *my_progress = target_progress;
+            let Ok(target_progress) = progress_query.get(event.target).cloned() else {
+                continue;
+            };
+            if let Ok(mut my_progress) = progress_query.get_mut(event.entity) {
+                *my_progress = target_progress;
+            }
         }
     }
 }
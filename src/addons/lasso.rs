@@ -1,9 +1,11 @@
 use crate::bike::{Bicycle, Player};
 use crate::bike_config::ForBicycle;
-use crate::game_state::{DespawnMe, RaceState};
+use crate::arena::{closest_opponent, ItemHitEvent};
+use crate::elimination::Eliminated;
+use crate::game_state::{race_running, DespawnMe, RaceConfig, RaceState, RaceType};
 use crate::ranking::{Progress, Rank};
+use crate::team::Team;
 use bevy::prelude::*;
-use rand::random;
 
 pub struct LassoPlugin;
 
@@ -12,11 +14,10 @@ impl Plugin for LassoPlugin {
         app.add_systems(
             Update,
             (
-                (player_lasso_control_system, fire_lasso_system)
-                    .run_if(in_state(RaceState::Playing)),
+                player_lasso_control_system.run_if(in_state(RaceState::Playing)),
+                fire_lasso_system.run_if(race_running),
                 move_to_target_system,
                 lasso_hit_system,
-                ai_lasso_control_system,
             ),
         )
         .add_event::<MovedToTargetEvent>()
@@ -24,18 +25,8 @@ impl Plugin for LassoPlugin {
     }
 }
 
-#[derive(Debug, Component)]
-pub struct LassoAddon {
-    ai_timer: Timer,
-}
-
-impl Default for LassoAddon {
-    fn default() -> Self {
-        Self {
-            ai_timer: Timer::from_seconds(random::<f32>() * 5.0, TimerMode::Once),
-        }
-    }
-}
+#[derive(Debug, Component, Default)]
+pub struct LassoAddon;
 
 pub const LASOO_SPEED: f32 = 0.1;
 
@@ -47,7 +38,7 @@ pub struct Lasso {
 
 #[derive(Debug, Event)]
 pub struct FireLassoEvent {
-    by: Entity,
+    pub by: Entity,
 }
 
 #[derive(Debug, Component)]
@@ -84,51 +75,40 @@ pub fn player_lasso_control_system(
     }
 }
 
-pub fn ai_lasso_control_system(
-    mut commands: Commands,
-    mut query: Query<(
-        Entity,
-        &mut LassoAddon,
-        &GlobalTransform,
-        &ForBicycle,
-        &Parent,
-    )>,
-    mut is_not_player_query: Query<&Bicycle, Without<Player>>,
-    mut events: EventWriter<FireLassoEvent>,
-    time: Res<Time>,
-) {
-    for (entity, mut lasso, transform, for_bicycle, parent) in query.iter_mut() {
-        if let Ok(_) = is_not_player_query.get(for_bicycle.0) {
-            if lasso.ai_timer.tick(time.delta()).just_finished() {
-                events.send(FireLassoEvent { by: for_bicycle.0 });
-
-                commands.entity(parent.get()).remove_children(&[entity]);
-                commands.entity(entity).despawn_recursive();
-            }
-        }
-    }
-}
-
 pub fn fire_lasso_system(
     mut commands: Commands,
-    mut by_query: Query<(Entity, &Rank, &GlobalTransform)>,
-    mut target_query: Query<(Entity, &Rank, &GlobalTransform)>,
+    mut by_query: Query<(Entity, &Rank, &GlobalTransform, Option<&Team>)>,
+    mut target_query: Query<
+        (Entity, &Rank, &GlobalTransform, Option<&Team>),
+        Without<Eliminated>,
+    >,
     mut events: EventReader<FireLassoEvent>,
+    race_config: Res<RaceConfig>,
     assets: Res<AssetServer>,
 ) {
     for (event) in events.read() {
-        let (by, by_rank, by_transform) = by_query.get_mut(event.by).unwrap();
-
-        let mut target = None;
-
-        for (entity, rank, transform) in target_query.iter_mut() {
-            if rank.0 + 1 == by_rank.0 {
-                target = Some((entity, transform.translation()));
-                break;
-            }
-        }
-
-        if let Some((target_entity, target_position)) = target {
+        let Ok((by, by_rank, by_transform, by_team)) = by_query.get_mut(event.by) else {
+            continue;
+        };
+
+        let opponents = target_query
+            .iter()
+            .filter(|(.., team)| !Team::same(by_team, *team));
+        let target = if race_config.race_type == RaceType::Arena {
+            closest_opponent(
+                by,
+                by_transform,
+                opponents.map(|(entity, _, transform, _)| (entity, transform)),
+            )
+        } else {
+            // The closest racer ahead, teammates are skipped
+            opponents
+                .filter(|(_, rank, ..)| rank.0 < by_rank.0)
+                .max_by_key(|(_, rank, ..)| rank.0)
+                .map(|(entity, ..)| entity)
+        };
+
+        if let Some(target_entity) = target {
             commands.spawn((
                 DespawnMe,
                 Lasso {
@@ -154,12 +134,21 @@ pub fn fire_lasso_system(
 }
 
 pub fn move_to_target_system(
-    mut query: Query<(Entity, &MoveToTarget, &mut Transform)>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &MoveToTarget, &mut Transform, Has<Bicycle>)>,
     target_query: Query<(&GlobalTransform)>,
     mut events: EventWriter<MovedToTargetEvent>,
 ) {
-    for (entity, target, mut transform) in query.iter_mut() {
-        let target_transform = target_query.get(target.target).unwrap();
+    for (entity, target, mut transform, bicycle) in query.iter_mut() {
+        // The target can be gone, e.g. when it was knocked out of an elimination race
+        let Ok(target_transform) = target_query.get(target.target) else {
+            if bicycle {
+                commands.entity(entity).remove::<MoveToTarget>();
+            } else {
+                commands.entity(entity).despawn_recursive();
+            }
+            continue;
+        };
 
         let direction = target_transform.translation() - transform.translation;
         let distance = direction.length();
@@ -183,6 +172,8 @@ pub fn lasso_hit_system(
     lasso_query: Query<(&Lasso)>,
     moving_back_query: Query<(&LassoCaughtAndMovingBack)>,
     mut progress_query: Query<(&mut Progress)>,
+    mut hit_events: EventWriter<ItemHitEvent>,
+    race_config: Res<RaceConfig>,
     assets: Res<AssetServer>,
 ) {
     for event in events.read() {
@@ -190,6 +181,12 @@ pub fn lasso_hit_system(
             println!("Lasso hit target");
             commands.entity(event.entity).despawn_recursive();
 
+            // There is no progress to swap in a battle, the lasso pops a balloon instead
+            if race_config.race_type == RaceType::Arena {
+                hit_events.send(ItemHitEvent { target: lasso.target });
+                continue;
+            }
+
             commands.entity(lasso.target).insert((
                 MoveToTarget {
                     target: lasso.by,
@@ -211,9 +208,12 @@ pub fn lasso_hit_system(
                 .entity(event.entity)
                 .remove::<(LassoCaughtAndMovingBack, MoveToTarget)>();
 
-            let my_progress = progress_query.get_mut(event.target).unwrap().clone();
-            let mut target_progress = progress_query.get_mut(event.entity).unwrap();
-            *target_progress = my_progress;
+            let Ok(my_progress) = progress_query.get(event.target).cloned() else {
+                continue;
+            };
+            if let Ok(mut target_progress) = progress_query.get_mut(event.entity) {
+                *target_progress = my_progress;
+            }
         }
     }
 }
@@ -1,16 +1,46 @@
+use bevy::audio::Volume;
 use bevy::prelude::*;
-use crate::bike::BicycleParams;
+use crate::bike::{BicycleParams, Player};
 use crate::bike_config::ForBicycle;
 
 #[derive(Debug, Component)]
 pub struct RocketAddon {
     timer: Timer,
+    /// Bots hold on to their rocket until there's a straight, the player's rocket fires right away
+    pub ignited: bool,
 }
 
 impl Default for RocketAddon {
     fn default() -> Self {
         Self {
             timer: Timer::from_seconds(5.0, TimerMode::Once),
+            ignited: false,
+        }
+    }
+}
+
+pub fn ignite_rocket(
+    commands: &mut Commands,
+    entity: Entity,
+    rocket: &mut RocketAddon,
+    assets: &Res<AssetServer>,
+) {
+    rocket.ignited = true;
+    commands.entity(entity).insert(AudioBundle {
+        source: assets.load("sounds/rocket.mp3"),
+        settings: PlaybackSettings::REMOVE.with_volume(Volume::new(0.5)),
+    });
+}
+
+pub fn ignite_player_rocket_system(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut RocketAddon, &ForBicycle), Added<RocketAddon>>,
+    player_query: Query<(), With<Player>>,
+    assets: Res<AssetServer>,
+) {
+    for (entity, mut rocket_addon, for_bicycle) in query.iter_mut() {
+        if player_query.contains(for_bicycle.0) {
+            ignite_rocket(&mut commands, entity, &mut rocket_addon, &assets);
         }
     }
 }
@@ -21,9 +51,12 @@ pub fn despawn_rocket_system(
     time: Res<Time>,
 ) {
     for (entity, mut rocket_addon, parent) in query.iter_mut() {
+        if !rocket_addon.ignited {
+            continue;
+        }
         if rocket_addon.timer.tick(time.delta()).just_finished() {
             commands.entity(parent.get()).remove_children(&[entity]);
             commands.entity(entity).despawn_recursive();
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,230 @@
+use crate::addons::giraffe::Poo;
+use crate::ai::skill::AiSkill;
+use crate::bike::Bicycle;
+use crate::slow::Slow;
+use crate::waypoint::WaypointAi;
+use avian2d::math::Vector;
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+/// How far ahead bots look for hazards, on top of the distance they cover in `SENSE_TIME` seconds
+const SENSE_DISTANCE: f32 = 3.0;
+const SENSE_TIME: f32 = 0.8;
+/// Width of the corridor in front of the bike that is checked for poo and other bikes
+const CORRIDOR_WIDTH: f32 = 2.0;
+/// Angles of the rays used to look for slow zones and to check how much room there is to the sides
+const AHEAD_RAY_ANGLE: f32 = 15.0;
+const ROOM_RAY_ANGLE: f32 = 35.0;
+/// Below this much free space on a side, bots won't try to pass something on that side
+const MIN_ROOM: f32 = 2.5;
+
+const POO_AVOIDANCE: f32 = 1.2;
+const OVERTAKE_AVOIDANCE: f32 = 0.8;
+const SLOW_AVOIDANCE: f32 = 0.5;
+
+/// What the bot sensed around itself, applied on top of the racing line in
+/// [`crate::waypoint::follow_waypoint`]
+#[derive(Component, Debug)]
+pub struct AiAvoidance {
+    /// Added to the steering, positive steers left
+    pub steer: f32,
+    /// Multiplier for the target speed, used to queue behind a bike when there's no room to pass
+    pub speed: f32,
+}
+
+impl Default for AiAvoidance {
+    fn default() -> Self {
+        Self {
+            steer: 0.0,
+            speed: 1.0,
+        }
+    }
+}
+
+struct RayProbe {
+    distance: f32,
+    slow: bool,
+}
+
+fn probe(
+    spatial_query: &SpatialQuery,
+    origin: Vec2,
+    direction: Vec2,
+    range: f32,
+    filter: &SpatialQueryFilter,
+    slow_query: &Query<&Slow>,
+) -> RayProbe {
+    let hit = Dir2::new(direction).ok().and_then(|direction| {
+        spatial_query.cast_ray(
+            Vector::new(origin.x, origin.y),
+            direction,
+            range,
+            true,
+            filter.clone(),
+        )
+    });
+
+    match hit {
+        Some(hit) => RayProbe {
+            distance: hit.time_of_impact,
+            slow: slow_query.contains(hit.entity),
+        },
+        None => RayProbe {
+            distance: range,
+            slow: false,
+        },
+    }
+}
+
+/// Free space along `direction` up to the next wall. Slow zones, poo, pickups and other bikes
+/// don't take away room, those are looked for separately.
+fn wall_room(
+    spatial_query: &SpatialQuery,
+    origin: Vec2,
+    direction: Vec2,
+    range: f32,
+    filter: &SpatialQueryFilter,
+    wall_query: &Query<&RigidBody>,
+) -> f32 {
+    let is_wall = |entity: Entity| {
+        wall_query
+            .get(entity)
+            .is_ok_and(|body| *body == RigidBody::Static)
+    };
+    Dir2::new(direction)
+        .ok()
+        .and_then(|direction| {
+            spatial_query.cast_ray_predicate(
+                Vector::new(origin.x, origin.y),
+                direction,
+                range,
+                true,
+                filter.clone(),
+                &is_wall,
+            )
+        })
+        .map_or(range, |hit| hit.time_of_impact)
+}
+
+/// Returns 1.0 to steer left or -1.0 to steer right around something that is `side` units
+/// to the left of the bike, preferring whichever side has room
+fn dodge_direction(side: f32, room_left: f32, room_right: f32) -> f32 {
+    let preferred = if side > 0.0 { -1.0 } else { 1.0 };
+    let preferred_room = if preferred > 0.0 { room_left } else { room_right };
+    let other_room = if preferred > 0.0 { room_right } else { room_left };
+
+    if preferred_room < MIN_ROOM && other_room > preferred_room {
+        -preferred
+    } else {
+        preferred
+    }
+}
+
+pub fn sense_hazards_system(
+    mut bike_query: Query<
+        (Entity, &Transform, &LinearVelocity, &AiSkill, &mut AiAvoidance),
+        With<WaypointAi>,
+    >,
+    spatial_query: SpatialQuery,
+    poo_query: Query<&GlobalTransform, With<Poo>>,
+    other_bike_query: Query<(&GlobalTransform, &LinearVelocity), With<Bicycle>>,
+    slow_query: Query<&Slow>,
+    wall_query: Query<&RigidBody>,
+) {
+    for (entity, transform, velocity, skill, mut avoidance) in bike_query.iter_mut() {
+        let position = transform.translation.xy();
+        let forward = transform.up().xy();
+        let left = forward.perp();
+        let speed = velocity.length();
+        let range = SENSE_DISTANCE + speed * SENSE_TIME;
+        let filter = SpatialQueryFilter::from_excluded_entities([entity]);
+
+        let ray = |angle: f32| {
+            probe(
+                &spatial_query,
+                position,
+                Vec2::from_angle(angle.to_radians()).rotate(forward),
+                range,
+                &filter,
+                &slow_query,
+            )
+        };
+
+        let room = |angle: f32| {
+            wall_room(
+                &spatial_query,
+                position,
+                Vec2::from_angle(angle.to_radians()).rotate(forward),
+                range,
+                &filter,
+                &wall_query,
+            )
+        };
+
+        let room_left = room(ROOM_RAY_ANGLE);
+        let room_right = room(-ROOM_RAY_ANGLE);
+
+        let mut steer = 0.0;
+        let mut speed_factor: f32 = 1.0;
+
+        let corridor = Collider::rectangle(CORRIDOR_WIDTH, range);
+        let corridor_center = position + forward * range / 2.0;
+        let corridor_rotation = Vec2::Y.angle_between(forward);
+
+        for hit in spatial_query.shape_intersections(
+            &corridor,
+            Vector::new(corridor_center.x, corridor_center.y),
+            corridor_rotation,
+            filter.clone(),
+        ) {
+            if let Ok(poo) = poo_query.get(hit) {
+                let offset = poo.translation().xy() - position;
+                let urgency = 1.0 - (offset.dot(forward) / range).clamp(0.0, 1.0);
+
+                steer += dodge_direction(offset.dot(left), room_left, room_right)
+                    * urgency
+                    * POO_AVOIDANCE;
+            } else if let Ok((other, other_velocity)) = other_bike_query.get(hit) {
+                let offset = other.translation().xy() - position;
+                let closing_speed = speed - other_velocity.dot(forward);
+
+                // Only bikes we are catching up to are in the way
+                if closing_speed <= 0.0 {
+                    continue;
+                }
+
+                if room_left < MIN_ROOM && room_right < MIN_ROOM {
+                    // No way past, queue up behind instead of ramming it
+                    speed_factor = speed_factor.min(other_velocity.length() / speed.max(0.1));
+                } else {
+                    let urgency = 1.0 - (offset.dot(forward) / range).clamp(0.0, 1.0);
+                    steer += dodge_direction(offset.dot(left), room_left, room_right)
+                        * urgency
+                        * OVERTAKE_AVOIDANCE
+                        * skill.overtaking;
+                }
+            }
+        }
+
+        let on_slow = spatial_query
+            .point_intersections(Vector::new(position.x, position.y), filter.clone())
+            .iter()
+            .any(|entity| slow_query.contains(*entity));
+
+        let ahead = ray(0.0);
+        if !on_slow && ahead.slow {
+            let ahead_left = ray(AHEAD_RAY_ANGLE);
+            let ahead_right = ray(-AHEAD_RAY_ANGLE);
+            let urgency = 1.0 - ahead.distance / range;
+
+            if !ahead_left.slow && room_left >= MIN_ROOM {
+                steer += urgency * SLOW_AVOIDANCE;
+            } else if !ahead_right.slow && room_right >= MIN_ROOM {
+                steer -= urgency * SLOW_AVOIDANCE;
+            }
+        }
+
+        avoidance.steer = steer.clamp(-1.0, 1.0);
+        avoidance.speed = speed_factor.clamp(0.3, 1.0);
+    }
+}
@@ -0,0 +1,95 @@
+use crate::ai::racing_line::{RacingLine, WALL_MARGIN};
+use crate::ai::skill::AiSkill;
+use crate::bike::Bicycle;
+use crate::map::TrackWalls;
+use crate::ranking::{Progress, Rank, NEXT_CHECKPOINT_DISTANCE};
+use crate::team::Team;
+use crate::waypoint::{Waypoint, WaypointAi};
+use bevy::prelude::*;
+
+/// How far behind a racer may be for the bot to defend against it
+const BLOCK_RANGE: f32 = 8.0;
+/// Racers further to the side than this are on a different part of the track
+const MAX_CHASER_SIDE: f32 = 4.0;
+/// The furthest a bot moves away from the racing line to block
+const MAX_BLOCK_OFFSET: f32 = 2.0;
+/// Units per second the bot moves sideways, so it changes lanes instead of jerking over
+const BLOCK_SHIFT_SPEED: f32 = 1.5;
+/// Blocking fades out over this distance before the checkpoint radius so the bot still reaches it
+const CHECKPOINT_FADE: f32 = 4.0;
+
+/// Defensive driving against a racer close behind, applied on top of the racing line in
+/// [`crate::waypoint::follow_waypoint`]
+#[derive(Component, Debug, Default)]
+pub struct AiBlocking {
+    /// Sideways offset from the racing line, positive is left
+    pub offset: f32,
+    /// Distance to the racer the bot is currently defending against
+    pub chaser: Option<f32>,
+}
+
+/// Looks for the racer directly behind and moves the bot toward its line, within the walls
+/// and only as far as the next checkpoint allows
+pub fn blocking_system(
+    mut bot_query: Query<
+        (
+            Entity,
+            &Transform,
+            &Rank,
+            &Progress,
+            &AiSkill,
+            &mut AiBlocking,
+            Option<&Team>,
+        ),
+        With<WaypointAi>,
+    >,
+    racer_query: Query<(Entity, &GlobalTransform, &Rank, Option<&Team>), With<Bicycle>>,
+    waypoint_query: Query<&Waypoint>,
+    racing_line: Res<RacingLine>,
+    walls: Res<TrackWalls>,
+    time: Res<Time>,
+) {
+    if racing_line.is_empty() {
+        return;
+    }
+
+    for (bike, transform, rank, progress, skill, mut blocking, team) in bot_query.iter_mut() {
+        let position = transform.translation.xy();
+        let forward = transform.up().xy();
+        let left = forward.perp();
+
+        let chaser = racer_query
+            .iter()
+            .filter(|(entity, _, other_rank, other_team)| {
+                *entity != bike && other_rank.0 == rank.0 + 1 && !Team::same(team, *other_team)
+            })
+            .map(|(_, other, ..)| other.translation().xy() - position)
+            .find(|offset| {
+                let behind = -offset.dot(forward);
+                behind > 0.0 && behind < BLOCK_RANGE && offset.dot(left).abs() < MAX_CHASER_SIDE
+            });
+
+        // Only bots that defend at all drop traps for the racer behind them
+        blocking.chaser = chaser
+            .filter(|_| skill.blocking > 0.0)
+            .map(|offset| offset.length());
+
+        let checkpoint_fade = ((progress.distance_to_next_checkpoint - NEXT_CHECKPOINT_DISTANCE)
+            / CHECKPOINT_FADE)
+            .clamp(0.0, 1.0);
+
+        let mut desired = 0.0;
+        if let (Some(offset), Ok(waypoint)) = (chaser, waypoint_query.get(progress.next_checkpoint)) {
+            // Room is measured from the racing line since that is what the offset is relative to
+            let line_point = racing_line.points[racing_line.nearest(position, waypoint.index)];
+            let side = offset.dot(left).clamp(-MAX_BLOCK_OFFSET, MAX_BLOCK_OFFSET);
+            let room = (walls.free_distance(line_point, left * side.signum()) - WALL_MARGIN)
+                .max(0.0);
+
+            desired = side.signum() * side.abs().min(room) * skill.blocking * checkpoint_fade;
+        }
+
+        let max_shift = BLOCK_SHIFT_SPEED * time.delta_seconds();
+        blocking.offset += (desired - blocking.offset).clamp(-max_shift, max_shift);
+    }
+}
@@ -0,0 +1,249 @@
+use crate::addons::giraffe::{spawn_poo, Giraffe};
+use crate::addons::hook::{FireHookEvent, HookAddon};
+use crate::addons::lasso::{FireLassoEvent, LassoAddon};
+use crate::addons::rocket::{ignite_rocket, RocketAddon};
+use crate::ai::blocking::AiBlocking;
+use crate::ai::racing_line::RacingLine;
+use crate::ai::skill::AiSkill;
+use crate::arena::RAM_RANGE;
+use crate::bike::{Bicycle, ModContainer};
+use crate::game_state::{RaceConfig, RaceType};
+use crate::ranking::{Progress, Rank};
+use crate::team::Team;
+use crate::waypoint::{Waypoint, WaypointAi};
+use bevy::prelude::*;
+
+/// Hook and lasso move slowly, further away targets get out of reach before they are caught
+pub const HOOK_RANGE: f32 = 12.0;
+/// A rival closer behind than this is worth dropping poo for
+pub const POO_RANGE: f32 = 6.0;
+/// How far to the side of our line a rival behind may be for the poo to still be in its way
+const POO_LANE_WIDTH: f32 = 1.5;
+/// The racing line has to allow this fraction of the max speed for this long to count as a straight
+const STRAIGHT_SPEED: f32 = 0.95;
+const STRAIGHT_LENGTH: f32 = 15.0;
+
+/// Everything the item policy needs to know about the race around a bot
+#[derive(Debug, Default)]
+pub struct ItemSituation {
+    /// Distance to the racer directly ahead, which is who hooks and lassos go for
+    pub target_ahead: Option<f32>,
+    /// Distance to the closest rival behind us on our line
+    pub rival_behind: Option<f32>,
+    /// Whether the racing line ahead is a straight, in battles whether someone is right in front
+    /// of us to ram
+    pub on_straight: bool,
+}
+
+/// What a bot carries in its mod container
+enum HeldItem {
+    Hook,
+    Lasso,
+    Giraffe,
+    Rocket,
+}
+
+impl HeldItem {
+    /// The item policy:
+    /// - hook and lasso only when the racer ahead is within range
+    /// - poo only when a rival is close behind on our line
+    /// - rocket only on a straight
+    ///
+    /// Less skilled bots are sloppier with the ranges and sometimes just fire blindly.
+    fn should_use(&self, situation: &ItemSituation, skill: &AiSkill) -> bool {
+        if skill.fires_blindly() {
+            return true;
+        }
+
+        let range = (2.0 - skill.item_smartness) * skill.aggression;
+        match self {
+            HeldItem::Hook | HeldItem::Lasso => situation
+                .target_ahead
+                .is_some_and(|distance| distance < HOOK_RANGE * range),
+            HeldItem::Giraffe => situation
+                .rival_behind
+                .is_some_and(|distance| distance < POO_RANGE * range),
+            HeldItem::Rocket => situation.on_straight,
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct AiItemState {
+    /// Bots only think about their items every so often, smarter bots more often
+    decision: Timer,
+}
+
+impl AiItemState {
+    pub fn new(skill: &AiSkill) -> Self {
+        Self {
+            decision: Timer::from_seconds(
+                1.5 - skill.item_smartness * 1.3,
+                TimerMode::Repeating,
+            ),
+        }
+    }
+}
+
+pub fn ai_item_system(
+    mut commands: Commands,
+    mut bot_query: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &Rank,
+            &Progress,
+            &AiSkill,
+            &mut AiItemState,
+            &AiBlocking,
+            &Children,
+            Option<&Team>,
+        ),
+        With<WaypointAi>,
+    >,
+    racer_query: Query<(Entity, &GlobalTransform, &Rank, Option<&Team>), With<Bicycle>>,
+    container_query: Query<&Children, With<ModContainer>>,
+    mut item_query: Query<(
+        Has<HookAddon>,
+        Has<LassoAddon>,
+        Option<&mut Giraffe>,
+        Option<&mut RocketAddon>,
+    )>,
+    waypoint_query: Query<&Waypoint>,
+    racing_line: Res<RacingLine>,
+    race_config: Res<RaceConfig>,
+    mut hook_events: EventWriter<FireHookEvent>,
+    mut lasso_events: EventWriter<FireLassoEvent>,
+    assets: Res<AssetServer>,
+    time: Res<Time>,
+) {
+    for (bike, transform, rank, progress, skill, mut state, blocking, children, team) in
+        bot_query.iter_mut()
+    {
+        if !state.decision.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        let Some(container) = children.first() else {
+            continue;
+        };
+        let Ok(container_children) = container_query.get(*container) else {
+            continue;
+        };
+
+        let held = container_children.iter().find_map(|entity| {
+            let (hook, lasso, giraffe, rocket) = item_query.get(*entity).ok()?;
+            let item = if hook {
+                HeldItem::Hook
+            } else if lasso {
+                HeldItem::Lasso
+            } else if giraffe.is_some() {
+                HeldItem::Giraffe
+            } else if rocket.is_some_and(|rocket| !rocket.ignited) {
+                HeldItem::Rocket
+            } else {
+                return None;
+            };
+            Some((*entity, item))
+        });
+
+        let Some((item_entity, item)) = held else {
+            continue;
+        };
+
+        let position = transform.translation().xy();
+        let forward = transform.up().xy();
+
+        // In battles everyone is a rival and hooks and lassos go for the closest one
+        let arena = race_config.race_type == RaceType::Arena;
+
+        // Teammates are neither targets nor worth dropping poo for
+        let opponents = || {
+            racer_query.iter().filter(move |(entity, .., other_team)| {
+                *entity != bike && !Team::same(team, *other_team)
+            })
+        };
+
+        let target_ahead = if arena {
+            opponents()
+                .map(|(_, other, ..)| other.translation().xy().distance(position))
+                .reduce(f32::min)
+        } else {
+            opponents()
+                .filter(|(_, _, other_rank, _)| other_rank.0 < rank.0)
+                .max_by_key(|(_, _, other_rank, _)| other_rank.0)
+                .map(|(_, other, ..)| other.translation().xy().distance(position))
+        };
+
+        let rival_behind = opponents()
+            .filter(|(_, _, other_rank, _)| arena || other_rank.0 > rank.0)
+            .filter_map(|(_, other, ..)| {
+                let offset = other.translation().xy() - position;
+                let behind = -offset.dot(forward);
+                (behind > 0.0 && offset.dot(forward.perp()).abs() < POO_LANE_WIDTH)
+                    .then_some(behind)
+            })
+            // A bot blocking someone is moving onto their line, so it drops traps for them too
+            .chain(blocking.chaser)
+            .reduce(f32::min);
+
+        let on_straight = if arena {
+            opponents().any(|(_, other, ..)| {
+                let offset = other.translation().xy() - position;
+                offset.dot(forward) > 0.0
+                    && offset.length() < RAM_RANGE
+                    && offset.dot(forward.perp()).abs() < POO_LANE_WIDTH
+            })
+        } else {
+            !racing_line.is_empty()
+            && waypoint_query
+                .get(progress.next_checkpoint)
+                .is_ok_and(|waypoint| {
+                    let nearest = racing_line.nearest(position, waypoint.index);
+                    racing_line.speed_ahead(nearest, STRAIGHT_LENGTH) >= STRAIGHT_SPEED
+                })
+        };
+
+        let situation = ItemSituation {
+            target_ahead,
+            rival_behind,
+            on_straight,
+        };
+
+        if !item.should_use(&situation, skill) {
+            continue;
+        }
+
+        match item {
+            HeldItem::Hook => {
+                hook_events.send(FireHookEvent { by: bike });
+                remove_item(&mut commands, *container, item_entity);
+            }
+            HeldItem::Lasso => {
+                lasso_events.send(FireLassoEvent { by: bike });
+                remove_item(&mut commands, *container, item_entity);
+            }
+            HeldItem::Giraffe => {
+                if let Ok((_, _, Some(mut giraffe), _)) = item_query.get_mut(item_entity) {
+                    if giraffe.poo_left > 0 {
+                        giraffe.poo_left -= 1;
+                        spawn_poo(&mut commands, transform, team.copied(), &assets);
+                    }
+                    if giraffe.poo_left == 0 {
+                        remove_item(&mut commands, *container, item_entity);
+                    }
+                }
+            }
+            HeldItem::Rocket => {
+                if let Ok((_, _, _, Some(mut rocket))) = item_query.get_mut(item_entity) {
+                    ignite_rocket(&mut commands, item_entity, &mut rocket, &assets);
+                }
+            }
+        }
+    }
+}
+
+fn remove_item(commands: &mut Commands, container: Entity, item: Entity) {
+    commands.entity(container).remove_children(&[item]);
+    commands.entity(item).despawn_recursive();
+}
@@ -0,0 +1,7 @@
+pub mod avoidance;
+pub mod blocking;
+pub mod items;
+pub mod racing_line;
+pub mod rivals;
+pub mod skill;
+pub mod stuck;
@@ -0,0 +1,205 @@
+use crate::map::TrackWalls;
+use crate::waypoint::Waypoint;
+use bevy::prelude::*;
+
+/// How many points are sampled from the spline between two waypoints
+pub const SAMPLES_PER_WAYPOINT: usize = 10;
+/// How far the line may be pulled away from the spline towards the apex. This has to stay well
+/// below [`crate::ranking::NEXT_CHECKPOINT_DISTANCE`] or bots would miss checkpoints.
+pub const MAX_APEX_OFFSET: f32 = 2.5;
+/// Distance the line keeps to the track walls
+pub const WALL_MARGIN: f32 = 1.2;
+const SMOOTHING_ITERATIONS: usize = 40;
+/// Curvature (1 / radius) up to which a corner can be taken at full speed
+const FULL_SPEED_CURVATURE: f32 = 0.12;
+const MIN_SPEED: f32 = 0.35;
+/// How much of the max speed a bike can shed per unit of distance when braking
+const BRAKING_PER_UNIT: f32 = 0.06;
+
+/// A smoothed line through the track waypoints, precomputed when the map is loaded.
+/// The bots follow this instead of steering at the raw waypoint corners.
+#[derive(Resource, Debug, Default)]
+pub struct RacingLine {
+    pub points: Vec<Vec2>,
+    /// Target speed at each point as a fraction of the bikes max speed
+    pub speeds: Vec<f32>,
+    /// The [`Waypoint::index`] of each waypoint in track order, the points from
+    /// `i * SAMPLES_PER_WAYPOINT` lead up to the waypoint at position `i + 1`
+    waypoint_indices: Vec<usize>,
+}
+
+impl RacingLine {
+    pub fn new(waypoints: &[(usize, Vec2)], walls: &TrackWalls) -> Self {
+        let positions: Vec<Vec2> = waypoints.iter().map(|(_, position)| *position).collect();
+        let spline = sample_spline(&positions);
+        let points = pull_towards_apex(&spline, walls);
+        let speeds = speed_profile(&points);
+
+        Self {
+            points,
+            speeds,
+            waypoint_indices: waypoints.iter().map(|(index, _)| *index).collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    fn wrap(&self, idx: isize) -> usize {
+        idx.rem_euclid(self.points.len() as isize) as usize
+    }
+
+    /// Finds the point closest to `position`, only looking at the part of the line that
+    /// leads up to the waypoint with `next_waypoint_index` so parallel parts of the track are ignored
+    pub fn nearest(&self, position: Vec2, next_waypoint_index: usize) -> usize {
+        let waypoint_pos = self
+            .waypoint_indices
+            .iter()
+            .position(|index| *index == next_waypoint_index)
+            .unwrap_or(0) as isize;
+
+        let samples = SAMPLES_PER_WAYPOINT as isize;
+        ((waypoint_pos - 2) * samples..=(waypoint_pos + 1) * samples)
+            .map(|idx| self.wrap(idx))
+            .min_by(|a, b| {
+                self.points[*a]
+                    .distance_squared(position)
+                    .total_cmp(&self.points[*b].distance_squared(position))
+            })
+            .unwrap_or(0)
+    }
+
+    /// Walks `distance` along the line starting from `idx`
+    pub fn ahead(&self, idx: usize, distance: f32) -> usize {
+        let mut walked = 0.0;
+        let mut current = idx;
+        while walked < distance {
+            let next = self.wrap(current as isize + 1);
+            walked += self.points[current].distance(self.points[next]);
+            current = next;
+            if current == idx {
+                break;
+            }
+        }
+        current
+    }
+
+    /// The lowest target speed between `idx` and `distance` further down the line
+    pub fn speed_ahead(&self, idx: usize, distance: f32) -> f32 {
+        let end = self.ahead(idx, distance);
+        let mut speed = self.speeds[idx];
+        let mut current = idx;
+        while current != end {
+            current = self.wrap(current as isize + 1);
+            speed = speed.min(self.speeds[current]);
+        }
+        speed
+    }
+}
+
+/// Closed catmull-rom spline through all waypoints
+fn sample_spline(positions: &[Vec2]) -> Vec<Vec2> {
+    let len = positions.len();
+    let mut points = Vec::with_capacity(len * SAMPLES_PER_WAYPOINT);
+    for i in 0..len {
+        let p0 = positions[(i + len - 1) % len];
+        let p1 = positions[i];
+        let p2 = positions[(i + 1) % len];
+        let p3 = positions[(i + 2) % len];
+        for s in 0..SAMPLES_PER_WAYPOINT {
+            let t = s as f32 / SAMPLES_PER_WAYPOINT as f32;
+            let t2 = t * t;
+            let t3 = t2 * t;
+            points.push(
+                0.5 * (2.0 * p1
+                    + (p2 - p0) * t
+                    + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+                    + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3),
+            );
+        }
+    }
+    points
+}
+
+/// Repeatedly relaxes every point towards its neighbours. This straightens the line and cuts
+/// towards the inside of corners, while the offset from the spline stays limited by the walls.
+fn pull_towards_apex(spline: &[Vec2], walls: &TrackWalls) -> Vec<Vec2> {
+    let len = spline.len();
+    let mut points = spline.to_vec();
+
+    for _ in 0..SMOOTHING_ITERATIONS {
+        for i in 0..len {
+            let prev = points[(i + len - 1) % len];
+            let next = points[(i + 1) % len];
+            let relaxed = points[i].lerp((prev + next) / 2.0, 0.5);
+
+            let offset = relaxed - spline[i];
+            let length = offset.length();
+            if length < f32::EPSILON {
+                points[i] = relaxed;
+                continue;
+            }
+            let direction = offset / length;
+            let room = walls.free_distance(spline[i], direction) - WALL_MARGIN;
+            let allowed = length.min(MAX_APEX_OFFSET).min(room.max(0.0));
+            points[i] = spline[i] + direction * allowed;
+        }
+    }
+
+    points
+}
+
+/// Calculates how fast each point can be taken based on the curvature of the line, and makes
+/// sure bots start braking early enough for the slow points
+fn speed_profile(points: &[Vec2]) -> Vec<f32> {
+    let len = points.len();
+    let mut speeds: Vec<f32> = (0..len)
+        .map(|i| {
+            let prev = points[(i + len - 1) % len];
+            let current = points[i];
+            let next = points[(i + 1) % len];
+            let segment = (current - prev).length() + (next - current).length();
+            if segment < f32::EPSILON {
+                return 1.0;
+            }
+            let angle = (current - prev).angle_between(next - current).abs();
+            let curvature = 2.0 * angle / segment;
+            if curvature <= FULL_SPEED_CURVATURE {
+                1.0
+            } else {
+                (FULL_SPEED_CURVATURE / curvature).sqrt().max(MIN_SPEED)
+            }
+        })
+        .collect();
+
+    // Two passes so the braking zone before the start line wraps around correctly
+    for _ in 0..2 {
+        for i in (0..len).rev() {
+            let next = (i + 1) % len;
+            let distance = points[i].distance(points[next]);
+            speeds[i] = speeds[i].min(speeds[next] + BRAKING_PER_UNIT * distance);
+        }
+    }
+
+    speeds
+}
+
+pub fn build_racing_line(
+    mut commands: Commands,
+    waypoint_query: Query<(&Waypoint, &Transform)>,
+    walls: Res<TrackWalls>,
+) {
+    let mut waypoints: Vec<(usize, Vec2)> = waypoint_query
+        .iter()
+        .map(|(waypoint, transform)| (waypoint.index, transform.translation.xy()))
+        .collect();
+    waypoints.sort_by_key(|(index, _)| *index);
+
+    // Arena maps have no waypoints and nothing to follow
+    if waypoints.is_empty() {
+        commands.insert_resource(RacingLine::default());
+        return;
+    }
+    commands.insert_resource(RacingLine::new(&waypoints, &walls));
+}
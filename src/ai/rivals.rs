@@ -0,0 +1,212 @@
+use crate::ai::skill::AiSkill;
+use crate::bike_config::addon::Addon;
+use crate::bike_config::frame::BikeFrame;
+use crate::bike_config::{BikeConfig, CharacterConfig, Hat, RearWheel, Skin};
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Personality {
+    /// Fires items early and often
+    Aggressive,
+    /// Sticks to the racing line and rarely makes mistakes
+    CleanLine,
+    /// Cuts corners hard and carries too much speed, which sometimes goes wrong
+    ShortcutTaker,
+    /// Holds its line instead of making room
+    Blocker,
+}
+
+impl Personality {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Personality::Aggressive => "Aggressive",
+            Personality::CleanLine => "Clean Line",
+            Personality::ShortcutTaker => "Shortcut Taker",
+            Personality::Blocker => "Blocker",
+        }
+    }
+
+    /// Adjusts the difficulty preset to the personality
+    pub fn apply(&self, skill: AiSkill) -> AiSkill {
+        match self {
+            Personality::Aggressive => AiSkill {
+                aggression: 1.6,
+                ..skill
+            },
+            Personality::CleanLine => AiSkill {
+                steering_precision: skill.steering_precision.lerp(1.0, 0.5),
+                mistake_probability: skill.mistake_probability * 0.3,
+                overtaking: 0.8,
+                ..skill
+            },
+            Personality::ShortcutTaker => AiSkill {
+                corner_cutting: 1.4,
+                cornering_speed: skill.cornering_speed * 1.08,
+                mistake_probability: skill.mistake_probability * 2.0 + 0.02,
+                ..skill
+            },
+            Personality::Blocker => AiSkill {
+                overtaking: 0.3,
+                blocking: (skill.blocking * 1.5).min(1.0),
+                ..skill
+            },
+        }
+    }
+}
+
+/// A named bot with a fixed look and driving style
+#[derive(Debug)]
+pub struct Rival {
+    pub name: &'static str,
+    pub personality: Personality,
+    pub skin: Skin,
+    pub hat: Hat,
+    pub frame: BikeFrame,
+    pub rear_wheel: RearWheel,
+    pub addon: Addon,
+}
+
+impl Rival {
+    pub fn config(&self) -> CharacterConfig {
+        CharacterConfig {
+            skin: self.skin.clone(),
+            hat: self.hat.clone(),
+            bike: BikeConfig {
+                rear_wheel: self.rear_wheel.clone(),
+                frame: self.frame.clone(),
+                addon: self.addon.clone(),
+            },
+        }
+    }
+}
+
+/// Identifies which rival of the [`ROSTER`] a bot is
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RivalId(pub usize);
+
+pub const ROSTER: [Rival; 12] = [
+    Rival {
+        name: "Iris",
+        personality: Personality::CleanLine,
+        skin: Skin::Bob,
+        hat: Hat::Crown,
+        frame: BikeFrame::Princess,
+        rear_wheel: RearWheel::Motorcycle,
+        addon: Addon::None,
+    },
+    Rival {
+        name: "Pupil Pete",
+        personality: Personality::Aggressive,
+        skin: Skin::Bob,
+        hat: Hat::Propeller,
+        frame: BikeFrame::Flames,
+        rear_wheel: RearWheel::Tank,
+        addon: Addon::Hook,
+    },
+    Rival {
+        name: "Cornelia",
+        personality: Personality::ShortcutTaker,
+        skin: Skin::Weyefu,
+        hat: Hat::TopHat,
+        frame: BikeFrame::Fast,
+        rear_wheel: RearWheel::Icecube,
+        addon: Addon::None,
+    },
+    Rival {
+        name: "Lash LaRue",
+        personality: Personality::Aggressive,
+        skin: Skin::Weyefu,
+        hat: Hat::None,
+        frame: BikeFrame::Banana,
+        rear_wheel: RearWheel::Skateboard,
+        addon: Addon::Lasso,
+    },
+    Rival {
+        name: "Retina Rita",
+        personality: Personality::Blocker,
+        skin: Skin::Weyetleye,
+        hat: Hat::PaperBag,
+        frame: BikeFrame::Banana,
+        rear_wheel: RearWheel::Tank,
+        addon: Addon::Giraffe,
+    },
+    Rival {
+        name: "Blinky",
+        personality: Personality::ShortcutTaker,
+        skin: Skin::Weyetleye,
+        hat: Hat::Propeller,
+        frame: BikeFrame::Fast,
+        rear_wheel: RearWheel::Skateboard,
+        addon: Addon::None,
+    },
+    Rival {
+        name: "Fly Guy",
+        personality: Personality::CleanLine,
+        skin: Skin::Fleye,
+        hat: Hat::TopHat,
+        frame: BikeFrame::Flames,
+        rear_wheel: RearWheel::Motorcycle,
+        addon: Addon::None,
+    },
+    Rival {
+        name: "Buzz",
+        personality: Personality::Blocker,
+        skin: Skin::Fleye,
+        hat: Hat::Crown,
+        frame: BikeFrame::Princess,
+        rear_wheel: RearWheel::Tank,
+        addon: Addon::Rocket,
+    },
+    Rival {
+        name: "Hiss Ssstare",
+        personality: Personality::Aggressive,
+        skin: Skin::Snek,
+        hat: Hat::TopHat,
+        frame: BikeFrame::Flames,
+        rear_wheel: RearWheel::Icecube,
+        addon: Addon::Giraffe,
+    },
+    Rival {
+        name: "Slinky",
+        personality: Personality::ShortcutTaker,
+        skin: Skin::Snek,
+        hat: Hat::None,
+        frame: BikeFrame::Fast,
+        rear_wheel: RearWheel::Skateboard,
+        addon: Addon::None,
+    },
+    Rival {
+        name: "Eye-Tee Phone Home",
+        personality: Personality::CleanLine,
+        skin: Skin::EyeT,
+        hat: Hat::PaperBag,
+        frame: BikeFrame::Princess,
+        rear_wheel: RearWheel::Motorcycle,
+        addon: Addon::None,
+    },
+    Rival {
+        name: "Optic Oscar",
+        personality: Personality::Blocker,
+        skin: Skin::EyeT,
+        hat: Hat::Crown,
+        frame: BikeFrame::Banana,
+        rear_wheel: RearWheel::Tank,
+        addon: Addon::Lasso,
+    },
+];
+
+/// Picks `count` rivals in random order, skipping the ones that look like the player.
+/// If more bots are requested than there are rivals, the roster is used again.
+pub fn pick_rivals(count: usize, player_skin: &Skin) -> Vec<RivalId> {
+    let mut available: Vec<RivalId> = ROSTER
+        .iter()
+        .enumerate()
+        .filter(|(_, rival)| &rival.skin != player_skin)
+        .map(|(idx, _)| RivalId(idx))
+        .collect();
+    available.shuffle(&mut thread_rng());
+
+    available.iter().copied().cycle().take(count).collect()
+}
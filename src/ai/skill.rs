@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
+
+/// The difficulty presets that can be selected in the race setup
+#[derive(Debug, Clone, Copy, Default, Sequence, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AiDifficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Expert,
+}
+
+impl AiDifficulty {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AiDifficulty::Easy => "Easy",
+            AiDifficulty::Normal => "Normal",
+            AiDifficulty::Hard => "Hard",
+            AiDifficulty::Expert => "Expert",
+        }
+    }
+
+    pub fn skill(&self) -> AiSkill {
+        match self {
+            AiDifficulty::Easy => AiSkill {
+                reaction_delay: 0.3,
+                steering_precision: 0.4,
+                cornering_speed: 0.8,
+                mistake_probability: 0.08,
+                item_smartness: 0.2,
+                corner_cutting: 1.0,
+                overtaking: 1.0,
+                aggression: 1.0,
+                blocking: 0.0,
+                drift_turbo: 0.0,
+            },
+            AiDifficulty::Normal => AiSkill {
+                reaction_delay: 0.15,
+                steering_precision: 0.7,
+                cornering_speed: 0.95,
+                mistake_probability: 0.03,
+                item_smartness: 0.5,
+                corner_cutting: 1.0,
+                overtaking: 1.0,
+                aggression: 1.0,
+                blocking: 0.0,
+                drift_turbo: 0.0,
+            },
+            AiDifficulty::Hard => AiSkill {
+                reaction_delay: 0.06,
+                steering_precision: 0.9,
+                cornering_speed: 1.05,
+                mistake_probability: 0.01,
+                item_smartness: 0.8,
+                corner_cutting: 1.0,
+                overtaking: 1.0,
+                aggression: 1.0,
+                blocking: 0.6,
+                drift_turbo: 0.6,
+            },
+            AiDifficulty::Expert => AiSkill {
+                reaction_delay: 0.0,
+                steering_precision: 1.0,
+                cornering_speed: 1.15,
+                mistake_probability: 0.0,
+                item_smartness: 1.0,
+                corner_cutting: 1.0,
+                overtaking: 1.0,
+                aggression: 1.0,
+                blocking: 1.0,
+                drift_turbo: 1.0,
+            },
+        }
+    }
+}
+
+/// Controls how well a bot drives, see [`AiDifficulty::skill`] for the presets
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct AiSkill {
+    /// Seconds between the bot re-evaluating where to steer
+    pub reaction_delay: f32,
+    /// 0 steers sloppily and overshoots, 1 steers exactly at the target
+    pub steering_precision: f32,
+    /// Multiplier for the speed the bot dares to take into a corner
+    pub cornering_speed: f32,
+    /// Chance per second to make a driving mistake
+    pub mistake_probability: f32,
+    /// 0 fires items whenever, 1 waits until the item is actually useful
+    pub item_smartness: f32,
+    /// Multiplier for how far ahead on the racing line the bot aims, higher cuts corners harder
+    pub corner_cutting: f32,
+    /// How eagerly the bot swerves out to pass a slower bike instead of holding its line
+    pub overtaking: f32,
+    /// Multiplier for the ranges in which the bot considers its items useful
+    pub aggression: f32,
+    /// How far the bot moves over to defend against a racer close behind, 0 never blocks
+    pub blocking: f32,
+    /// Chance to drift through a corner for a mini-turbo, 0 never drifts
+    pub drift_turbo: f32,
+}
+
+impl Default for AiSkill {
+    fn default() -> Self {
+        AiDifficulty::default().skill()
+    }
+}
+
+impl AiSkill {
+    /// Rolled every time a bot thinks about its item, dumb bots sometimes fire no matter the situation
+    pub fn fires_blindly(&self) -> bool {
+        rand::random::<f32>() < (1.0 - self.item_smartness) * 0.25
+    }
+}
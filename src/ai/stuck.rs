@@ -0,0 +1,165 @@
+use crate::addons::giraffe::PooCollision;
+use crate::bike::{BicycleControl, DriftTurbo};
+use crate::game_state::RaceConfig;
+use crate::ranking::Progress;
+use crate::waypoint::{Waypoint, WaypointAi};
+use avian2d::prelude::LinearVelocity;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_egui::egui::{Align2, Id, Window};
+use bevy_egui::{egui, EguiContexts};
+use rand::random;
+
+/// Bots that moved less than this within one window count as stuck
+const STUCK_DISTANCE: f32 = 1.5;
+const STUCK_WINDOW: f32 = 2.0;
+const REVERSE_TIME: f32 = 1.0;
+/// After this many reverse attempts in a row the bot is put back on the last checkpoint
+const MAX_REVERSE_ATTEMPTS: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Recovery {
+    Driving,
+    Reversing { turn: f32 },
+}
+
+#[derive(Component, Debug)]
+pub struct StuckDetector {
+    window: Timer,
+    window_start: Vec2,
+    recovery: Recovery,
+    reverse: Timer,
+    attempts: usize,
+}
+
+impl StuckDetector {
+    /// `start` is where the bot starts, so the first window is measured from there
+    pub fn new(start: Vec2) -> Self {
+        Self {
+            window: Timer::from_seconds(STUCK_WINDOW, TimerMode::Repeating),
+            window_start: start,
+            recovery: Recovery::Driving,
+            reverse: Timer::from_seconds(REVERSE_TIME, TimerMode::Once),
+            attempts: 0,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TrapCount {
+    pub stuck: usize,
+    pub respawns: usize,
+}
+
+/// How often bots got stuck on each track, shown in debug builds to find spots that need fixing
+#[derive(Resource, Debug, Default)]
+pub struct StuckStats(pub HashMap<String, TrapCount>);
+
+/// Watches the bots progress and runs the recovery stages when they don't move anymore:
+/// first reverse and turn, then retry, and if that keeps failing respawn at the last checkpoint
+pub fn stuck_recovery_system(
+    mut bot_query: Query<
+        (
+            &mut StuckDetector,
+            &mut BicycleControl,
+            &mut Transform,
+            &mut LinearVelocity,
+            &Progress,
+            Option<&mut DriftTurbo>,
+        ),
+        (With<WaypointAi>, Without<PooCollision>),
+    >,
+    waypoint_query: Query<(Entity, &Waypoint, &Transform), Without<WaypointAi>>,
+    race_config: Res<RaceConfig>,
+    mut stats: ResMut<StuckStats>,
+    time: Res<Time>,
+) {
+    for (mut detector, mut control, mut transform, mut velocity, progress, turbo) in
+        bot_query.iter_mut()
+    {
+        let position = transform.translation.xy();
+
+        if let Recovery::Reversing { turn } = detector.recovery {
+            control.acceleration = -1.0;
+            control.turn = turn;
+            control.drift = false;
+            if let Some(mut turbo) = turbo {
+                turbo.cancel();
+            }
+
+            if detector.reverse.tick(time.delta()).finished() {
+                detector.recovery = Recovery::Driving;
+                detector.window.reset();
+                detector.window_start = position;
+            }
+            continue;
+        }
+
+        if !detector.window.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        let moved = position.distance(detector.window_start);
+        detector.window_start = position;
+
+        if moved >= STUCK_DISTANCE {
+            detector.attempts = 0;
+            continue;
+        }
+
+        let count = stats.0.entry(race_config.map.clone()).or_default();
+        count.stuck += 1;
+        detector.attempts += 1;
+
+        if detector.attempts <= MAX_REVERSE_ATTEMPTS {
+            detector.recovery = Recovery::Reversing {
+                turn: if random() { 1.0 } else { -1.0 },
+            };
+            detector.reverse.reset();
+            continue;
+        }
+
+        let last_checkpoint = waypoint_query
+            .iter()
+            .find(|(_, waypoint, _)| waypoint.next == Some(progress.next_checkpoint));
+        let next_checkpoint = waypoint_query.get(progress.next_checkpoint);
+
+        if let (Some((_, _, last)), Ok((_, _, next))) = (last_checkpoint, next_checkpoint) {
+            let direction = (next.translation - last.translation).xy();
+            transform.translation = last.translation.xy().extend(transform.translation.z);
+            transform.rotation = Quat::from_rotation_z(Vec2::Y.angle_between(direction));
+            velocity.0 = Vec2::ZERO;
+
+            count.respawns += 1;
+            detector.attempts = 0;
+            detector.window_start = transform.translation.xy();
+            info!("Respawned stuck bot on {}", race_config.map);
+        }
+    }
+}
+
+pub fn stuck_debug_ui(mut egui: EguiContexts, stats: Res<StuckStats>) {
+    if !cfg!(debug_assertions) || stats.0.is_empty() {
+        return;
+    }
+
+    Window::new("Stuck Bots")
+        .id(Id::new("Stuck Bots"))
+        .anchor(Align2::LEFT_BOTTOM, [0.0, 0.0])
+        .resizable(false)
+        .show(egui.ctx_mut(), |ui| {
+            egui::Grid::new("Stuck Bots Grid").show(ui, |ui| {
+                ui.label("Track");
+                ui.label("Stuck");
+                ui.label("Respawned");
+                ui.end_row();
+
+                for (track, count) in stats.0.iter() {
+                    ui.label(track);
+                    ui.label(count.stuck.to_string());
+                    ui.label(count.respawns.to_string());
+                    ui.end_row();
+                }
+            });
+        });
+}
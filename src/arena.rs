@@ -0,0 +1,377 @@
+use crate::addons::giraffe::PooCollision;
+use crate::addons::rocket::RocketAddon;
+use crate::ai::avoidance::{sense_hazards_system, AiAvoidance};
+use crate::ai::rivals::RivalId;
+use crate::ai::stuck::stuck_recovery_system;
+use crate::bike::{spawn_bikes, Bicycle, BicycleControl, ModContainer, Player};
+use crate::bike_config::addon::AddonComponent;
+use crate::cup::CupSlot;
+use crate::elimination::{Elimination, Eliminated, KnockedOut};
+use crate::game_state::{race_running, DespawnMe, GameState, RaceConfig, RaceState, RaceType};
+use crate::item_pickup::ItemPickup;
+use crate::lap_timer::RaceClock;
+use crate::map::TrackWalls;
+use crate::ranking::{FinishTime, Rank};
+use crate::team::Team;
+use crate::waypoint::WaypointAi;
+use avian2d::prelude::{Collision, LinearVelocity};
+use bevy::prelude::*;
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+
+/// Balloons every racer starts a battle with
+pub const START_BALLOONS: usize = 3;
+/// Seconds until a taken item pickup is back in a battle
+pub const ARENA_ITEM_RESPAWN: f32 = 0.4;
+/// After losing a balloon a racer can't lose another one for this long, so one hit only costs one
+const HIT_COOLDOWN: f32 = 1.5;
+const BALLOON_COLORS: [Color; START_BALLOONS] = [
+    Color::srgb(0.9, 0.2, 0.2),
+    Color::srgb(0.95, 0.8, 0.2),
+    Color::srgb(0.2, 0.5, 0.9),
+];
+/// Height above the bike the balloons float at, above the name tag
+const BALLOON_OFFSET: f32 = 2.2;
+const BALLOON_SPACING: f32 = 0.5;
+/// Bots look this far ahead for walls and turn away from them
+const WALL_LOOKAHEAD: f32 = 3.0;
+/// Bots only fire a rocket when someone to ram is this close in front of them
+pub const RAM_RANGE: f32 = 8.0;
+
+pub struct ArenaPlugin;
+
+impl Plugin for ArenaPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ArenaSpawns>()
+            .add_event::<ItemHitEvent>()
+            .add_systems(OnEnter(GameState::Race), spawn_balloons.after(spawn_bikes))
+            .add_systems(
+                Update,
+                (
+                    (
+                        rocket_ram_system,
+                        balloon_hit_system,
+                        arena_rank_system,
+                        arena_ai_system
+                            .after(sense_hazards_system)
+                            .before(stuck_recovery_system),
+                    )
+                        .run_if(race_running.and_then(in_arena)),
+                    update_balloons,
+                )
+                    .run_if(in_state(GameState::Race)),
+            );
+    }
+}
+
+/// Where the bikes start on arena maps, set with `class="spawn"` circles
+#[derive(Resource, Debug, Default)]
+pub struct ArenaSpawns(pub Vec<Vec2>);
+
+/// Sent when an item hits a racer, which costs a balloon in battles
+#[derive(Event, Debug)]
+pub struct ItemHitEvent {
+    pub target: Entity,
+}
+
+#[derive(Component, Debug)]
+pub struct Balloons {
+    pub left: usize,
+    cooldown: Timer,
+}
+
+impl Default for Balloons {
+    fn default() -> Self {
+        let mut cooldown = Timer::from_seconds(HIT_COOLDOWN, TimerMode::Once);
+        cooldown.tick(cooldown.duration());
+        Self {
+            left: START_BALLOONS,
+            cooldown,
+        }
+    }
+}
+
+/// One balloon floating above a bike. Like the name tag this is not a child of the bike so it
+/// doesn't rotate and mirror with it.
+#[derive(Component, Debug)]
+pub struct Balloon {
+    bike: Entity,
+    index: usize,
+}
+
+fn in_arena(race_config: Res<RaceConfig>) -> bool {
+    race_config.race_type == RaceType::Arena
+}
+
+/// The closest other racer, which is who hooks and lassos go for in battles
+pub fn closest_opponent<'a>(
+    by: Entity,
+    by_transform: &GlobalTransform,
+    racers: impl Iterator<Item = (Entity, &'a GlobalTransform)>,
+) -> Option<Entity> {
+    let position = by_transform.translation().xy();
+    racers
+        .filter(|(entity, _)| *entity != by)
+        .min_by(|(_, a), (_, b)| {
+            a.translation()
+                .xy()
+                .distance_squared(position)
+                .total_cmp(&b.translation().xy().distance_squared(position))
+        })
+        .map(|(entity, _)| entity)
+}
+
+fn spawn_balloons(
+    mut commands: Commands,
+    bike_query: Query<Entity, With<Bicycle>>,
+    race_config: Res<RaceConfig>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    if race_config.race_type != RaceType::Arena {
+        return;
+    }
+
+    let mesh = Mesh2dHandle(meshes.add(Circle::new(0.22)));
+    let colors = BALLOON_COLORS.map(|color| materials.add(color));
+
+    for bike in bike_query.iter() {
+        commands.entity(bike).insert(Balloons::default());
+
+        for (index, color) in colors.iter().enumerate() {
+            commands.spawn((
+                Name::new("Balloon"),
+                MaterialMesh2dBundle {
+                    mesh: mesh.clone(),
+                    material: color.clone(),
+                    ..Default::default()
+                },
+                Balloon { bike, index },
+                DespawnMe,
+            ));
+        }
+    }
+}
+
+pub fn update_balloons(
+    mut commands: Commands,
+    mut balloon_query: Query<(Entity, &Balloon, &mut Transform)>,
+    bike_query: Query<(&GlobalTransform, &Balloons)>,
+) {
+    for (entity, balloon, mut transform) in balloon_query.iter_mut() {
+        match bike_query.get(balloon.bike) {
+            Ok((bike_transform, balloons)) if balloon.index < balloons.left => {
+                let position = bike_transform.translation();
+                let x = (balloon.index as f32 - (START_BALLOONS - 1) as f32 / 2.0) * BALLOON_SPACING;
+                transform.translation =
+                    Vec3::new(position.x + x, position.y + BALLOON_OFFSET, 1000.0);
+            }
+            _ => {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+/// Whether the bike's mod container holds a burning rocket
+fn has_ignited_rocket(
+    bike: Entity,
+    children_query: &Query<&Children>,
+    container_query: &Query<(), With<ModContainer>>,
+    rocket_query: &Query<&RocketAddon>,
+) -> bool {
+    let Some(container) = children_query
+        .get(bike)
+        .ok()
+        .and_then(|children| children.iter().find(|child| container_query.contains(**child)))
+    else {
+        return false;
+    };
+
+    children_query.get(*container).is_ok_and(|children| {
+        children
+            .iter()
+            .any(|child| rocket_query.get(*child).is_ok_and(|rocket| rocket.ignited))
+    })
+}
+
+/// Ramming someone with a burning rocket counts as a hit in battles
+pub fn rocket_ram_system(
+    mut collisions: EventReader<Collision>,
+    bike_query: Query<Option<&Team>, With<Bicycle>>,
+    children_query: Query<&Children>,
+    container_query: Query<(), With<ModContainer>>,
+    rocket_query: Query<&RocketAddon>,
+    mut hit_events: EventWriter<ItemHitEvent>,
+) {
+    for Collision(contact) in collisions.read() {
+        let (a, b) = (contact.entity1, contact.entity2);
+        let (Ok(a_team), Ok(b_team)) = (bike_query.get(a), bike_query.get(b)) else {
+            continue;
+        };
+        if Team::same(a_team, b_team) {
+            continue;
+        }
+
+        for (rammer, target) in [(a, b), (b, a)] {
+            if has_ignited_rocket(rammer, &children_query, &container_query, &rocket_query) {
+                hit_events.send(ItemHitEvent { target });
+            }
+        }
+    }
+}
+
+pub fn balloon_hit_system(
+    mut commands: Commands,
+    mut racer_query: Query<
+        (
+            Entity,
+            &mut Balloons,
+            &Name,
+            &CupSlot,
+            Option<&RivalId>,
+            Has<Player>,
+        ),
+        Without<Eliminated>,
+    >,
+    mut hit_events: EventReader<ItemHitEvent>,
+    mut elimination: ResMut<Elimination>,
+    mut next_race_state: ResMut<NextState<RaceState>>,
+    clock: Res<RaceClock>,
+    time: Res<Time>,
+) {
+    for (_, mut balloons, ..) in racer_query.iter_mut() {
+        balloons.cooldown.tick(time.delta());
+    }
+
+    let mut remaining = racer_query
+        .iter()
+        .filter(|(_, balloons, ..)| balloons.left > 0)
+        .count();
+
+    for event in hit_events.read() {
+        let Ok((entity, mut balloons, name, slot, rival, player)) =
+            racer_query.get_mut(event.target)
+        else {
+            continue;
+        };
+        if balloons.left == 0 || !balloons.cooldown.finished() {
+            continue;
+        }
+
+        balloons.left -= 1;
+        balloons.cooldown.reset();
+
+        if balloons.left > 0 {
+            continue;
+        }
+
+        elimination.knock_out(
+            &mut commands,
+            entity,
+            KnockedOut {
+                slot: *slot,
+                name: name.to_string(),
+                rival: rival.copied(),
+                player,
+                place: remaining,
+                time: clock.0,
+            },
+        );
+        remaining -= 1;
+
+        if player {
+            next_race_state.set(RaceState::Finished);
+        }
+    }
+}
+
+/// In battles whoever has the most balloons left leads
+pub fn arena_rank_system(
+    mut racer_query: Query<(&Balloons, &mut Rank), (Without<Eliminated>, Without<FinishTime>)>,
+) {
+    let mut all = racer_query.iter_mut().collect::<Vec<_>>();
+    all.sort_by_key(|(balloons, _)| std::cmp::Reverse(balloons.left));
+
+    for (i, (_, mut rank)) in all.into_iter().enumerate() {
+        rank.0 = i + 1;
+    }
+}
+
+/// Bots go for an item pickup while their hands are empty and hunt the closest opponent once
+/// they hold something, turning away from walls on the way
+pub fn arena_ai_system(
+    mut bot_query: Query<
+        (
+            Entity,
+            &Transform,
+            &LinearVelocity,
+            &AiAvoidance,
+            &Children,
+            &mut BicycleControl,
+            Option<&Team>,
+        ),
+        (With<WaypointAi>, Without<PooCollision>),
+    >,
+    racer_query: Query<
+        (Entity, &GlobalTransform, Option<&Team>),
+        (With<Bicycle>, Without<Eliminated>),
+    >,
+    pickup_query: Query<(&GlobalTransform, &ItemPickup)>,
+    container_query: Query<&Children, With<ModContainer>>,
+    addon_query: Query<(), With<AddonComponent>>,
+    walls: Res<TrackWalls>,
+) {
+    for (bike, transform, velocity, avoidance, children, mut control, team) in bot_query.iter_mut()
+    {
+        let position = transform.translation.xy();
+        let forward = transform.up().xy();
+
+        let holds_item = children
+            .first()
+            .and_then(|container| container_query.get(*container).ok())
+            .is_some_and(|items| items.iter().any(|item| addon_query.contains(*item)));
+
+        let closest = |targets: &mut dyn Iterator<Item = Vec2>| {
+            targets.min_by(|a, b| {
+                a.distance_squared(position)
+                    .total_cmp(&b.distance_squared(position))
+            })
+        };
+
+        let mut opponents = racer_query
+            .iter()
+            .filter(|(entity, _, other_team)| *entity != bike && !Team::same(team, *other_team))
+            .map(|(_, transform, _)| transform.translation().xy());
+        let mut pickups = pickup_query
+            .iter()
+            .filter(|(_, pickup)| pickup.item.is_some())
+            .map(|(transform, _)| transform.translation().xy());
+
+        let target = if holds_item {
+            closest(&mut opponents)
+        } else {
+            closest(&mut pickups).or_else(|| closest(&mut opponents))
+        };
+        let Some(target) = target else {
+            control.acceleration = 0.0;
+            control.turn = 0.0;
+            continue;
+        };
+
+        let direction = (target - position).normalize_or_zero();
+        let angle_deg = -direction.angle_between(forward).to_degrees();
+        let mut turn = angle_deg / 45.0 + avoidance.steer;
+        let mut acceleration = 1.0 - turn.abs().min(1.0) * 0.5;
+
+        // The arena has no racing line that keeps the bots off the walls
+        if walls.free_distance(position, forward) < WALL_LOOKAHEAD + velocity.length() * 0.3 {
+            let left = walls.free_distance(position, Vec2::from_angle(0.6).rotate(forward));
+            let right = walls.free_distance(position, Vec2::from_angle(-0.6).rotate(forward));
+            turn = if left > right { 1.0 } else { -1.0 };
+            acceleration = 0.4;
+        }
+
+        control.turn = turn.clamp(-1.0, 1.0);
+        control.acceleration = acceleration * avoidance.speed;
+    }
+}
@@ -1,11 +1,23 @@
 use crate::addons::giraffe::PooCollision;
 use crate::addons::rocket::RocketAddon;
+use crate::ai::avoidance::AiAvoidance;
+use crate::ai::blocking::AiBlocking;
+use crate::ai::items::AiItemState;
+use crate::ai::rivals::{pick_rivals, RivalId, ROSTER};
+use crate::ai::stuck::StuckDetector;
+use crate::arena::ArenaSpawns;
 use crate::bike_config::{
     BicycleMod, BicycleModTrait, BikeConfig, CharacterConfig, ForBicycle, PlayerConfig, Selectable,
     FRAME_OFFSET,
 };
+use crate::bump::Wobble;
+use crate::cup::{CupSession, CupSlot};
 use crate::game_state::{DespawnMe, GameState, RaceConfig};
-use crate::ranking::{Progress, Rank};
+use crate::ghost::Ghost;
+use crate::lap_timer::LapTimes;
+use crate::name_tag::spawn_name_tag;
+use crate::ranking::{FinishTime, Progress, Rank};
+use crate::slipstream::Slipstream;
 use crate::slow::Slow;
 use crate::waypoint::{Waypoint, WaypointAi};
 use avian2d::math::Vector;
@@ -16,7 +28,6 @@ use bevy_egui::egui;
 use bevy_egui::egui::{lerp, Id, Ui};
 use bevy_inspector_egui::inspector_egui_impls::InspectorPrimitive;
 use bevy_inspector_egui::reflect_inspector::InspectorUi;
-use rand::random;
 use std::any::Any;
 use std::f32::consts::PI;
 
@@ -38,29 +49,41 @@ pub fn spawn_bikes(
     children_query: Query<&Children>,
     menu: Res<State<GameState>>,
     race_config: Res<RaceConfig>,
+    cup: Res<CupSession>,
+    spawns: Res<ArenaSpawns>,
 ) {
     let menu = matches!(**menu, GameState::MainMenu);
 
-    let (first_waypoint_entity, first_waypoint, start_post) = waypoint
+    // Arena maps have no waypoints, the bikes start on the spawn points instead
+    let start = waypoint
         .iter()
         .sort_by_key::<&Waypoint, _>(|data| data.index)
-        .next()
-        .unwrap();
-
-    let (next_waypoint_entity, next_waypoint, next_waypoint_transfrom) =
-        waypoint.get(first_waypoint.next.unwrap()).unwrap();
+        .next();
+    let first_waypoint_entity = start.map_or(Entity::PLACEHOLDER, |(entity, ..)| entity);
+
+    // A cup keeps the same rivals and difficulty for all of its races
+    let (rivals, difficulty) = if race_config.plays_cup() && cup.active {
+        (cup.rivals.clone(), cup.cup.difficulty)
+    } else {
+        (
+            pick_rivals(race_config.bot_count(), &player_config.0.skin),
+            race_config.difficulty,
+        )
+    };
 
-    let direction = -(next_waypoint_transfrom.translation - start_post.translation).xy();
+    let mut spawn = |slot: usize, rival: Option<RivalId>, transform: Transform| {
+        let name = rival.map_or("You", |rival| ROSTER[rival.0].name);
 
-    let mut spawn = |player: bool, offset: Vec2| {
         let (player_id, container_id) = {
             let mut entity = commands.spawn((
-                Name::new(if player { "Player" } else { "Bot" }),
+                Name::new(name),
                 Bicycle,
                 BicycleControl {
                     turn: 0.0,
                     acceleration: 1.0,
+                    drift: false,
                 },
+                (DriftTurbo::default(), Slipstream::default()),
                 Progress {
                     next_checkpoint: first_waypoint_entity,
                     round: 0,
@@ -68,28 +91,37 @@ pub fn spawn_bikes(
                     distance_to_next_checkpoint: 0.0,
                 },
                 Rank(0),
+                CupSlot(slot),
+                LapTimes::default(),
                 DespawnMe,
                 RigidBody::Dynamic,
                 VisibilityBundle::default(),
                 Collider::capsule(GAME_BICYCLE_LENGTH / 20.0, GAME_BICYCLE_LENGTH),
-                // This was just the initial size I chose when first testing bike params
-                Mass(0.2 * 2.0),
                 ExternalForce::default(),
                 TransformBundle {
-                    local: start_post.clone()
-                        * Transform::from_translation(Vec3::new(offset.x, offset.y, 0.0))
-                        * Transform::from_rotation(Quat::from_rotation_z(
-                            -direction.angle_between(-Vec2::Y),
-                        )),
+                    local: transform,
                     ..Default::default()
                 },
-                LinearDamping(10.0),
-                AngularDamping(10.0),
+                (LinearDamping(10.0), AngularDamping(10.0)),
             ));
-            if player {
-                entity.insert(Player);
+            if let Some(rival) = rival {
+                let skill = ROSTER[rival.0]
+                    .personality
+                    .apply(difficulty.skill());
+                entity.insert((
+                    rival,
+                    WaypointAi::new(&skill),
+                    AiAvoidance::default(),
+                    AiBlocking::default(),
+                    AiItemState::new(&skill),
+                    StuckDetector::new(transform.translation.xy()),
+                    skill,
+                ));
             } else {
-                entity.insert(WaypointAi);
+                entity.insert(Player);
+            }
+            if let Some(team) = race_config.team_of(CupSlot(slot)) {
+                entity.insert(team);
             }
 
             let mut container_id = None;
@@ -112,45 +144,65 @@ pub fn spawn_bikes(
             (entity.id(), container_id.unwrap())
         };
 
-        if player {
-            apply_config(
-                &mut commands,
-                player_id,
-                container_id,
-                &player_config.0,
-                &asset_server,
-                &children_query,
-                menu,
-            );
-        } else {
-            let mut config: CharacterConfig = random();
-            while config.skin == player_config.0.skin {
-                config = random();
-            }
-            apply_config(
-                &mut commands,
-                player_id,
-                container_id,
-                &config,
-                &asset_server,
-                &children_query,
-                menu,
-            );
+        let config = match rival {
+            Some(rival) => ROSTER[rival.0].config(),
+            None => player_config.0.clone(),
+        };
+        apply_config(
+            &mut commands,
+            player_id,
+            container_id,
+            &config,
+            &asset_server,
+            &children_query,
+            menu,
+        );
+
+        let color = race_config
+            .team_of(CupSlot(slot))
+            .map_or(Color::WHITE, |team| team.color());
+        spawn_name_tag(&mut commands, player_id, name, color);
+    };
+
+    let Some((_, first_waypoint, start_post)) = start else {
+        // Everyone faces the middle of the arena, racers beyond the spawn count queue up behind
+        let center = spawns.0.iter().sum::<Vec2>() / spawns.0.len().max(1) as f32;
+        for i in 0..rivals.len() + 1 {
+            let Some(position) = spawns.0.get(i % spawns.0.len().max(1)) else {
+                warn!("Arena map has no spawn points");
+                return;
+            };
+            let facing = (center - *position).normalize_or(Vec2::Y);
+            let position = *position - facing * (i / spawns.0.len()) as f32 * 1.4;
+            let transform = Transform::from_translation(position.extend(0.0))
+                .with_rotation(Quat::from_rotation_z(Vec2::Y.angle_between(facing)));
+            spawn(i, i.checked_sub(1).map(|bot| rivals[bot]), transform);
         }
+        return;
     };
 
+    let (next_waypoint_entity, next_waypoint, next_waypoint_transfrom) =
+        waypoint.get(first_waypoint.next.unwrap()).unwrap();
+
+    let direction = -(next_waypoint_transfrom.translation - start_post.translation).xy();
     let direction_right = direction.normalize().rotate(Vec2::from_angle(PI / 2.0));
 
     // Places enemies in a F1 like  grid
-    for i in 0..race_config.ai_count + 1 {
+    for i in 0..rivals.len() + 1 {
         // This makes a mess but is better than bikes off the track
         let offset_i = usize::min(i, 8);
         let offset = direction.normalize() * (offset_i as f32 * 1.4)
             + direction_right * (offset_i as f32 % 2.0);
-        spawn(i == 0, offset);
+        let transform = *start_post
+            * Transform::from_translation(Vec3::new(offset.x, offset.y, 0.0))
+            * Transform::from_rotation(Quat::from_rotation_z(-direction.angle_between(-Vec2::Y)));
+        spawn(i, i.checked_sub(1).map(|bot| rivals[bot]), transform);
     }
 }
 
+// This was just the initial size I chose when first testing bike params
+pub const BASE_MASS: f32 = 0.2 * 2.0;
+
 pub const TEXTURE_BICYCLE_LENGTH: f32 = 1250.0;
 pub const GAME_BICYCLE_LENGTH: f32 = 2.0;
 
@@ -193,7 +245,10 @@ pub fn apply_config(
     {
         let mut entity_commands = commands.entity(entity);
 
-        entity_commands.insert(BicycleParams::default() * config.bike.frame.params());
+        entity_commands.insert((
+            BicycleParams::default() * config.params(),
+            Mass(config.mass()),
+        ));
     }
 
     if let Ok(children) = children_query.get(container_entity) {
@@ -315,6 +370,58 @@ pub fn spawn_selectable(
 pub struct BicycleControl {
     pub(crate) acceleration: f32,
     pub(crate) turn: f32,
+    /// Held through corners to slide and charge a mini-turbo, see [`DriftTurbo`]
+    pub(crate) drift: bool,
+}
+
+/// Sideways speed integrated over the drift needed for each mini-turbo tier
+const TURBO_TIERS: [f32; 3] = [1.0, 2.5, 4.0];
+/// Seconds the boost of each tier lasts
+const TURBO_TIMES: [f32; 3] = [0.5, 0.9, 1.3];
+const TURBO_SPEED: f32 = 1.3;
+const TURBO_ACCELERATION: f32 = 1.5;
+/// Grip is raised to this power while drifting, so the bike slides more
+const DRIFT_GRIP: f32 = 0.5;
+
+/// The charge of a drift, released into a short boost when the drift input is let go
+#[derive(Component, Debug)]
+pub struct DriftTurbo {
+    pub charge: f32,
+    boost: Timer,
+}
+
+impl Default for DriftTurbo {
+    fn default() -> Self {
+        let mut boost = Timer::from_seconds(0.0, TimerMode::Once);
+        boost.tick(boost.duration());
+        Self { charge: 0.0, boost }
+    }
+}
+
+impl DriftTurbo {
+    /// The tier the current charge would release, 0 if it is not enough for a boost yet
+    pub fn tier(&self) -> usize {
+        TURBO_TIERS
+            .iter()
+            .take_while(|threshold| self.charge >= **threshold)
+            .count()
+    }
+
+    pub fn boosting(&self) -> bool {
+        !self.boost.finished()
+    }
+
+    /// Loses the charge without a boost, for drifts that end without the racer letting go
+    pub fn cancel(&mut self) {
+        self.charge = 0.0;
+    }
+
+    fn release(&mut self) {
+        if let Some(time) = self.tier().checked_sub(1).map(|tier| TURBO_TIMES[tier]) {
+            self.boost = Timer::from_seconds(time, TimerMode::Once);
+        }
+        self.charge = 0.0;
+    }
 }
 
 #[derive(Component, Debug, Reflect)]
@@ -324,9 +431,41 @@ pub struct BicycleParams {
     pub turn: f32,
     // The lower, the driftier
     pub drift: f32,
+    /// Max speed multiplier set by the catch-up system
+    pub catch_up_speed: f32,
+    /// Above 1 the racer has a chance to get a better item from pickups
+    pub item_luck: f32,
 }
 
 pub const DEFAULT_MAX_SPEED: f32 = 5.0;
+/// The frame rate the handling was tuned at. Everything that used to happen once per frame is
+/// scaled by the frame time, so bikes handle the same at any frame rate.
+pub const REFERENCE_FPS: f32 = 60.0;
+/// Damping bikes coast towards when nobody accelerates
+const COAST_DAMPING: f32 = 3.0;
+
+/// The same effect over `delta` seconds as multiplying by `factor` on every frame at
+/// [`REFERENCE_FPS`]
+fn per_frame_factor(factor: f32, delta: f32) -> f32 {
+    factor.powf(delta * REFERENCE_FPS)
+}
+
+/// Radians to turn the bike by over `delta` seconds, bikes turn slower the slower they go
+fn turn_angle(turn: f32, params: &BicycleParams, forward_velocity: f32, delta: f32) -> f32 {
+    let slow_turn_factor = (forward_velocity / DEFAULT_MAX_SPEED).clamp(-1.0, 1.0);
+    turn.clamp(-1.0, 1.0) * params.turn * slow_turn_factor * delta * REFERENCE_FPS
+}
+
+/// Moves the damping towards [`COAST_DAMPING`] while coasting
+fn coast_damping(damping: f32, delta: f32) -> f32 {
+    FloatExt::lerp(damping, COAST_DAMPING, 1.0 - per_frame_factor(0.99, delta))
+}
+
+/// The fraction of its sideways velocity a bike keeps over `delta` seconds
+fn sideways_grip(params: &BicycleParams, delta: f32) -> f32 {
+    let drift = (1.0 - (0.08 * (1.0 / params.drift))).max(0.0);
+    per_frame_factor(drift, delta)
+}
 
 impl Default for BicycleParams {
     fn default() -> Self {
@@ -335,6 +474,8 @@ impl Default for BicycleParams {
             acceleration: 25.0,
             turn: 0.02,
             drift: 1.0,
+            catch_up_speed: 1.0,
+            item_luck: 1.0,
         }
     }
 }
@@ -348,6 +489,8 @@ impl std::ops::Mul<BicycleParams> for BicycleParams {
             acceleration: self.acceleration * rhs.acceleration,
             turn: self.turn * rhs.turn,
             drift: self.drift * rhs.drift,
+            catch_up_speed: self.catch_up_speed * rhs.catch_up_speed,
+            item_luck: self.item_luck * rhs.item_luck,
         }
     }
 }
@@ -366,6 +509,12 @@ impl InspectorPrimitive for BicycleParams {
         ui.label("Drift");
         ui.add(egui::Slider::new(&mut self.drift, 0.0..=10.0).text("Drift"));
 
+        ui.label("Catch-up Speed");
+        ui.add(egui::Slider::new(&mut self.catch_up_speed, 0.0..=2.0).text("Catch-up Speed"));
+
+        ui.label("Item Luck");
+        ui.add(egui::Slider::new(&mut self.item_luck, 0.0..=2.0).text("Item Luck"));
+
         false
     }
 
@@ -384,14 +533,33 @@ pub fn bike_controller_system(
         &mut ExternalForce,
         &mut LinearDamping,
         &Children,
+        &Mass,
+        Option<&DriftTurbo>,
+        Option<&Slipstream>,
+        Option<&Wobble>,
     )>,
     children_query: Query<&Children>,
-    has_rocket_query: Query<(&RocketAddon)>,
+    rocket_query: Query<(&RocketAddon)>,
     spatial_query: SpatialQuery,
     mut slow_query: Query<(&Slow)>,
+    time: Res<Time>,
 ) {
-    for (entity, control, params, velocity, mut transform, mut ext_force, mut damping, container) in
-        query.iter_mut()
+    let delta = time.delta_seconds();
+
+    for (
+        entity,
+        control,
+        params,
+        velocity,
+        mut transform,
+        mut ext_force,
+        mut damping,
+        container,
+        mass,
+        turbo,
+        slipstream,
+        wobble,
+    ) in query.iter_mut()
     {
         ext_force.clear();
 
@@ -400,7 +568,7 @@ pub fn bike_controller_system(
 
         let has_rocket = container_children
             .iter()
-            .any(|entity| has_rocket_query.get(*entity).is_ok());
+            .any(|entity| rocket_query.get(*entity).is_ok_and(|rocket| rocket.ignited));
 
         let intersections = spatial_query.point_intersections(
             Vector::new(transform.translation.x, transform.translation.y),
@@ -414,7 +582,7 @@ pub fn bike_controller_system(
             params.max_speed * 0.5
         } else {
             params.max_speed
-        };
+        } * params.catch_up_speed;
         let mut acceleration = if slow {
             params.acceleration * 0.5
         } else {
@@ -425,6 +593,13 @@ pub fn bike_controller_system(
             max_speed *= 2.0;
             acceleration *= 2.0;
         }
+        if let Some(slipstream) = slipstream {
+            max_speed *= 1.0 + slipstream.bonus;
+        }
+        if turbo.is_some_and(DriftTurbo::boosting) {
+            max_speed *= TURBO_SPEED;
+            acceleration *= TURBO_ACCELERATION;
+        }
 
         let bike_forward = (transform.rotation * Vec3::Y).xy();
 
@@ -441,31 +616,46 @@ pub fn bike_controller_system(
             control_acceleration_clamped = 0.0;
         }
 
-        let acceleration = control_acceleration_clamped * acceleration;
+        // The force grows with the mass, so weight only matters when bikes bump into each other
+        let acceleration = control_acceleration_clamped * acceleration * mass.0 / BASE_MASS;
         let current_rotation = transform.rotation * Vec3::Y;
         ext_force.apply_force(Vec2::new(acceleration, 0.0).rotate(current_rotation.xy()));
 
-        let slow_turn_factor = (forward_velocity / DEFAULT_MAX_SPEED).clamp(-1.0, 1.0);
-        let turn_clamped = control.turn.clamp(-1.0, 1.0);
-        let turn = turn_clamped * params.turn * slow_turn_factor;
-        transform.rotate_z(turn);
+        // The wobble is added on top of the input, so it is gone once the wobble is over
+        let turn = control.turn + wobble.map_or(0.0, Wobble::turn);
+        transform.rotate_z(turn_angle(turn, params, forward_velocity, delta));
 
         if control.acceleration == 0.0 {
-            **damping = FloatExt::lerp(**damping, 3.0, 0.01);
+            **damping = coast_damping(**damping, delta);
         } else {
             damping.0 = 0.0;
         }
 
         if slow {
-            damping.0 *= 1.2;
+            damping.0 *= per_frame_factor(1.2, delta);
         }
     }
 }
 
 /// Basically kills the orthogonal velocity of the bike, as explained here: https://youtu.be/DVHcOS1E5OQ?si=UgpKyHxYqsRehCeZ&t=559
-pub fn drift_factor_system(mut query: Query<(&mut LinearVelocity, &Transform, &BicycleParams)>) {
-    for (mut lin_vel, transform, params) in query.iter_mut() {
-        let drift = 1.0 - (0.08 * (1.0 / params.drift));
+/// While the drift input is held the bike slides more and the sideways velocity charges its
+/// mini-turbo.
+pub fn drift_factor_system(
+    mut query: Query<(
+        &mut LinearVelocity,
+        &Transform,
+        &BicycleParams,
+        Option<&BicycleControl>,
+        Option<&mut DriftTurbo>,
+    )>,
+    time: Res<Time>,
+) {
+    for (mut lin_vel, transform, params, control, turbo) in query.iter_mut() {
+        let drifting = control.is_some_and(|control| control.drift);
+        let mut drift = sideways_grip(params, time.delta_seconds());
+        if drifting {
+            drift = drift.powf(DRIFT_GRIP);
+        }
 
         let bike_forward = (transform.rotation * Vec3::Y).xy();
         let bike_right = (transform.rotation * Vec3::X).xy();
@@ -473,6 +663,15 @@ pub fn drift_factor_system(mut query: Query<(&mut LinearVelocity, &Transform, &B
         let forward = bike_forward * lin_vel.dot(bike_forward.xy());
         let right = bike_right * lin_vel.dot(bike_right.xy());
 
+        if let Some(mut turbo) = turbo {
+            turbo.boost.tick(time.delta());
+            if drifting {
+                turbo.charge += right.length() * time.delta_seconds();
+            } else if turbo.charge > 0.0 {
+                turbo.release();
+            }
+        }
+
         **lin_vel = forward + right * drift;
     }
 }
@@ -480,10 +679,20 @@ pub fn drift_factor_system(mut query: Query<(&mut LinearVelocity, &Transform, &B
 pub fn control_player(
     mut commands: Commands,
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut query: Query<(&mut BicycleControl), (With<Player>, Without<PooCollision>)>,
+    mut query: Query<
+        (&mut BicycleControl, Has<FinishTime>),
+        (With<Player>, Without<PooCollision>),
+    >,
 ) {
-    for (mut control) in query.iter_mut() {
+    for (mut control, finished) in query.iter_mut() {
         control.acceleration = 0.0;
+        control.turn = 0.0;
+        control.drift = false;
+        // Coast out after the finish line
+        if finished {
+            continue;
+        }
+
         if keyboard_input.pressed(KeyCode::KeyW) || keyboard_input.pressed(KeyCode::ArrowUp) {
             control.acceleration += 1.0;
         }
@@ -491,20 +700,22 @@ pub fn control_player(
             control.acceleration -= 1.0;
         }
 
-        control.turn = 0.0;
         if keyboard_input.pressed(KeyCode::KeyA) || keyboard_input.pressed(KeyCode::ArrowLeft) {
             control.turn += 1.0;
         }
         if keyboard_input.pressed(KeyCode::KeyD) || keyboard_input.pressed(KeyCode::ArrowRight) {
             control.turn -= 1.0;
         }
+
+        control.drift =
+            keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight);
     }
 }
 
 // if the bike is going to the left, mirror it vertically
 pub fn mirror_bike_system(
-    mut query: Query<(&mut Transform, &Children), With<Bicycle>>,
-    mut child_query: Query<(&mut Transform), Without<Bicycle>>,
+    mut query: Query<(&mut Transform, &Children), Or<(With<Bicycle>, With<Ghost>)>>,
+    mut child_query: Query<(&mut Transform), (Without<Bicycle>, Without<Ghost>)>,
 ) {
     for ((mut transform, children)) in query.iter_mut() {
         let rotation = transform.up().angle_between(Vec3::X);
@@ -544,3 +755,91 @@ pub fn apply_z_order(mut query: Query<(&GlobalTransform, &mut Transform), With<M
         transform.translation.z = i as f32 * 50.0;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Stands in for the physics step, with exact damping so only the bike systems depend on
+    /// the frame rate
+    fn integrate_system(
+        mut query: Query<(
+            &mut LinearVelocity,
+            &mut Transform,
+            &ExternalForce,
+            &LinearDamping,
+            &Mass,
+        )>,
+        time: Res<Time>,
+    ) {
+        let delta = time.delta_seconds();
+        for (mut velocity, mut transform, force, damping, mass) in query.iter_mut() {
+            velocity.0 += force.force() / mass.0 * delta;
+            velocity.0 *= (-damping.0 * delta).exp();
+            transform.translation += velocity.0.extend(0.0) * delta;
+        }
+    }
+
+    /// Coasts a bike from full speed through a full left turn for `seconds`, updating at `fps`.
+    /// Returns its velocity and heading.
+    fn simulate(fps: f64, seconds: f64) -> (Vec2, Vec2) {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<SpatialQueryPipeline>()
+            .add_systems(
+                Update,
+                (
+                    bike_controller_system,
+                    drift_factor_system,
+                    integrate_system,
+                )
+                    .chain(),
+            );
+
+        let bike = app
+            .world_mut()
+            .spawn((
+                BicycleControl {
+                    turn: 1.0,
+                    acceleration: 0.0,
+                    drift: false,
+                },
+                BicycleParams {
+                    drift: 0.5,
+                    ..Default::default()
+                },
+                LinearVelocity(Vec2::Y * DEFAULT_MAX_SPEED),
+                Transform::default(),
+                ExternalForce::default(),
+                LinearDamping(0.0),
+                Mass(BASE_MASS),
+            ))
+            .with_children(|bike| {
+                bike.spawn_empty().with_children(|container| {
+                    container.spawn_empty();
+                });
+            })
+            .id();
+
+        for _ in 0..(seconds * fps).round() as usize {
+            app.world_mut()
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs_f64(1.0 / fps));
+            app.update();
+        }
+
+        let world = app.world();
+        let heading = (world.get::<Transform>(bike).unwrap().rotation * Vec3::Y).xy();
+        (world.get::<LinearVelocity>(bike).unwrap().0, heading)
+    }
+
+    #[test]
+    fn handling_is_the_same_at_60_and_144_fps() {
+        let (browser_velocity, browser_heading) = simulate(60.0, 1.0);
+        let (monitor_velocity, monitor_heading) = simulate(144.0, 1.0);
+
+        assert!(browser_heading.angle_between(monitor_heading).abs() < 1e-2);
+        assert!(browser_velocity.distance(monitor_velocity) < DEFAULT_MAX_SPEED * 0.01);
+    }
+}
@@ -1,4 +1,3 @@
-use bevy::audio::{AudioBundle, Volume};
 use crate::addons::giraffe::Giraffe;
 use crate::addons::hook::HookAddon;
 use crate::addons::lasso::LassoAddon;
@@ -6,14 +5,15 @@ use crate::addons::rocket::RocketAddon;
 use crate::bike_config::{BicycleModTrait, FRAME_OFFSET};
 use bevy::ecs::system::EntityCommands;
 use bevy::math::Vec2;
-use bevy::prelude::{AssetServer, Component, PlaybackSettings, Res};
+use bevy::prelude::{AssetServer, Component, Res};
 use enum_iterator::Sequence;
 use rand_derive2::RandGen;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Component)]
 pub struct AddonComponent;
 
-#[derive(Debug, Clone, Default, Sequence, RandGen, PartialEq)]
+#[derive(Debug, Clone, Default, Sequence, RandGen, PartialEq, Serialize, Deserialize)]
 pub enum Addon {
     #[default]
     None,
@@ -68,13 +68,7 @@ impl BicycleModTrait for Addon {
                 commands.insert(LassoAddon::default());
             }
             Addon::Rocket => {
-                commands.insert((
-                    RocketAddon::default(),
-                    AudioBundle {
-                        source: assets.load("sounds/rocket.mp3"),
-                        settings: PlaybackSettings::REMOVE.with_volume(Volume::new(0.5)),
-                    },
-                ));
+                commands.insert(RocketAddon::default());
             }
         }
     }
@@ -5,8 +5,9 @@ use enum_iterator::{all, Sequence};
 use rand::prelude::IteratorRandom;
 use rand::thread_rng;
 use rand_derive2::RandGen;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Default, Sequence, RandGen, PartialEq)]
+#[derive(Debug, Clone, Default, Sequence, RandGen, PartialEq, Serialize, Deserialize)]
 pub enum BikeFrame {
     #[default]
     Fast,
@@ -43,6 +44,15 @@ impl BicycleModTrait for BikeFrame {
 }
 
 impl BikeFrame {
+    pub fn weight(&self) -> f32 {
+        match self {
+            BikeFrame::Fast => 1.0,
+            BikeFrame::Princess => 0.9,
+            BikeFrame::Banana => 0.85,
+            BikeFrame::Flames => 1.1,
+        }
+    }
+
     pub fn params(&self) -> BicycleParams {
         match self {
             BikeFrame::Fast => BicycleParams {
@@ -50,24 +60,28 @@ impl BikeFrame {
                 acceleration: 1.0,
                 turn: 1.0,
                 drift: 0.5,
+                ..Default::default()
             },
             BikeFrame::Princess => BicycleParams {
                 max_speed: 0.9,
                 acceleration: 1.8,
                 turn: 1.2,
                 drift: 1.5,
+                ..Default::default()
             },
             BikeFrame::Banana => BicycleParams {
                 max_speed: 0.8,
                 acceleration: 1.53,
                 turn: 1.0,
                 drift: 1.0,
+                ..Default::default()
             },
             BikeFrame::Flames => BicycleParams {
                 max_speed: 1.2,
                 acceleration: 1.0,
                 turn: 0.8,
                 drift: 1.00,
+                ..Default::default()
             },
         }
     }
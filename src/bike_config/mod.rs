@@ -1,6 +1,7 @@
 pub mod addon;
 pub mod frame;
 
+use crate::bike::{BicycleParams, BASE_MASS};
 use crate::bike_config::addon::Addon;
 use crate::bike_config::frame::BikeFrame;
 use bevy::ecs::system::EntityCommands;
@@ -10,6 +11,7 @@ use enum_iterator::{all, Sequence};
 use rand::prelude::IteratorRandom;
 use rand::thread_rng;
 use rand_derive2::RandGen;
+use serde::{Deserialize, Serialize};
 
 pub const FRAME_OFFSET: Vec2 = Vec2::new(0.0, 0.0);
 
@@ -84,14 +86,28 @@ where
     }
 }
 
-#[derive(Debug, Clone, Default, RandGen)]
+#[derive(Debug, Clone, Default, RandGen, Serialize, Deserialize)]
 pub struct CharacterConfig {
     pub skin: Skin,
     pub hat: Hat,
     pub bike: BikeConfig,
 }
 
-#[derive(Debug, Clone, Default, Sequence, RandGen, PartialEq)]
+impl CharacterConfig {
+    /// The handling of the whole build, frame and rear wheel combined with the rider's weight
+    pub fn params(&self) -> BicycleParams {
+        let mut params = self.bike.frame.params() * self.bike.rear_wheel.params();
+        params.acceleration /= self.skin.weight();
+        params
+    }
+
+    /// Heavier builds push lighter ones around when they bump into each other
+    pub fn mass(&self) -> f32 {
+        BASE_MASS * self.skin.weight() * self.bike.frame.weight() * self.bike.rear_wheel.weight()
+    }
+}
+
+#[derive(Debug, Clone, Default, Sequence, RandGen, PartialEq, Serialize, Deserialize)]
 pub enum Skin {
     #[default]
     Bob,
@@ -155,7 +171,21 @@ impl BicycleModTrait for Skin {
     }
 }
 
-#[derive(Debug, Clone, Default, Sequence, RandGen, PartialEq)]
+impl Skin {
+    /// Relative weight of the rider, heavier riders get going slower
+    pub fn weight(&self) -> f32 {
+        match self {
+            Skin::Bob => 1.0,
+            Skin::Weyefu => 0.95,
+            Skin::Weyetleye => 1.15,
+            Skin::Fleye => 0.85,
+            Skin::Snek => 0.9,
+            Skin::EyeT => 1.1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Sequence, RandGen, PartialEq, Serialize, Deserialize)]
 pub enum Hat {
     #[default]
     None,
@@ -227,14 +257,14 @@ impl BicycleModTrait for Hat {
     }
 }
 
-#[derive(Debug, Clone, Default, RandGen)]
+#[derive(Debug, Clone, Default, RandGen, Serialize, Deserialize)]
 pub struct BikeConfig {
     pub rear_wheel: RearWheel,
     pub frame: BikeFrame,
     pub addon: Addon,
 }
 
-#[derive(Debug, Clone, Default, Sequence, RandGen, PartialEq)]
+#[derive(Debug, Clone, Default, Sequence, RandGen, PartialEq, Serialize, Deserialize)]
 pub enum RearWheel {
     #[default]
     Motorcycle,
@@ -269,3 +299,48 @@ impl BicycleModTrait for RearWheel {
         5.0
     }
 }
+
+impl RearWheel {
+    pub fn weight(&self) -> f32 {
+        match self {
+            RearWheel::Motorcycle => 1.1,
+            RearWheel::Skateboard => 0.8,
+            RearWheel::Tank => 1.4,
+            RearWheel::Icecube => 1.0,
+        }
+    }
+
+    /// Multiplied with the frame params, see [`CharacterConfig::params`]
+    pub fn params(&self) -> BicycleParams {
+        match self {
+            RearWheel::Motorcycle => BicycleParams {
+                max_speed: 1.0,
+                acceleration: 1.0,
+                turn: 1.0,
+                drift: 1.0,
+                ..Default::default()
+            },
+            RearWheel::Skateboard => BicycleParams {
+                max_speed: 0.95,
+                acceleration: 1.3,
+                turn: 1.1,
+                drift: 0.9,
+                ..Default::default()
+            },
+            RearWheel::Tank => BicycleParams {
+                max_speed: 0.88,
+                acceleration: 0.85,
+                turn: 0.95,
+                drift: 1.6,
+                ..Default::default()
+            },
+            RearWheel::Icecube => BicycleParams {
+                max_speed: 1.03,
+                acceleration: 0.95,
+                turn: 1.15,
+                drift: 0.55,
+                ..Default::default()
+            },
+        }
+    }
+}
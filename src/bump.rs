@@ -0,0 +1,100 @@
+use crate::bike::{bike_controller_system, Bicycle, BASE_MASS};
+use crate::elimination::Eliminated;
+use crate::game_state::{race_running, GameState};
+use avian2d::prelude::{CollisionStarted, LinearVelocity, Mass};
+use bevy::prelude::*;
+
+/// Bumps slower than this are just rubbing and don't knock anyone around
+const MIN_BUMP_SPEED: f32 = 1.0;
+/// Fraction of the closing speed that is added on top of the physics bounce
+const KNOCKBACK: f32 = 0.6;
+/// Wobble strength per unit of closing speed, in turn input
+const WOBBLE_PER_SPEED: f32 = 0.15;
+const MAX_WOBBLE: f32 = 1.0;
+/// Seconds a bike of base mass wobbles, heavier bikes take longer to steady themselves
+const WOBBLE_TIME: f32 = 0.6;
+/// Swerves per second while wobbling
+const WOBBLE_FREQUENCY: f32 = 3.0;
+
+pub struct BumpPlugin;
+
+impl Plugin for BumpPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (bump_system, wobble_system.before(bike_controller_system))
+                .chain()
+                .run_if(in_state(GameState::Race).and_then(race_running)),
+        );
+    }
+}
+
+/// Makes a bike swerve after it was bumped into
+#[derive(Component, Debug)]
+pub struct Wobble {
+    timer: Timer,
+    strength: f32,
+}
+
+impl Wobble {
+    /// The swerve added to the racer's turn input by [`bike_controller_system`], fading out
+    /// until the bike is steady again
+    pub fn turn(&self) -> f32 {
+        let phase = self.timer.elapsed_secs() * WOBBLE_FREQUENCY * std::f32::consts::TAU;
+        phase.sin() * self.strength * self.timer.fraction_remaining()
+    }
+}
+
+/// Pushes bikes that run into each other apart, the lighter one is pushed further and wobbles
+/// harder. The heavier one takes longer to steady itself again, which is what light builds get
+/// in return.
+pub fn bump_system(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionStarted>,
+    mut bike_query: Query<
+        (&GlobalTransform, &mut LinearVelocity, &Mass),
+        (With<Bicycle>, Without<Eliminated>),
+    >,
+) {
+    for CollisionStarted(a, b) in collisions.read() {
+        let Ok([(a_transform, mut a_velocity, a_mass), (b_transform, mut b_velocity, b_mass)]) =
+            bike_query.get_many_mut([*a, *b])
+        else {
+            continue;
+        };
+
+        let direction = (b_transform.translation() - a_transform.translation())
+            .xy()
+            .normalize_or_zero();
+        let closing_speed = (a_velocity.0 - b_velocity.0).dot(direction);
+        if closing_speed < MIN_BUMP_SPEED {
+            continue;
+        }
+
+        let total_mass = a_mass.0 + b_mass.0;
+        let knockback = closing_speed * KNOCKBACK;
+        a_velocity.0 -= direction * knockback * b_mass.0 / total_mass;
+        b_velocity.0 += direction * knockback * a_mass.0 / total_mass;
+
+        for (entity, own_mass, other_mass) in [(*a, a_mass.0, b_mass.0), (*b, b_mass.0, a_mass.0)] {
+            let strength =
+                (closing_speed * WOBBLE_PER_SPEED * other_mass / own_mass).min(MAX_WOBBLE);
+            commands.entity(entity).insert(Wobble {
+                timer: Timer::from_seconds(WOBBLE_TIME * own_mass / BASE_MASS, TimerMode::Once),
+                strength,
+            });
+        }
+    }
+}
+
+pub fn wobble_system(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Wobble)>,
+    time: Res<Time>,
+) {
+    for (entity, mut wobble) in query.iter_mut() {
+        if wobble.timer.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Wobble>();
+        }
+    }
+}
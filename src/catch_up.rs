@@ -0,0 +1,98 @@
+use crate::bike::{Bicycle, BicycleParams, Player};
+use crate::game_state::RaceConfig;
+use crate::ranking::{Progress, Rank};
+use crate::waypoint::{TrackLength, Waypoint, WaypointAi};
+use bevy::prelude::*;
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
+
+/// Gap to the player along the track at which the catch-up effect is at full strength
+const FULL_EFFECT_GAP: f32 = 40.0;
+
+/// Rubber-banding that keeps the bots close to the player
+#[derive(Debug, Clone, Copy, Default, Sequence, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CatchUp {
+    #[default]
+    Off,
+    Light,
+    Strong,
+}
+
+impl CatchUp {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CatchUp::Off => "Off",
+            CatchUp::Light => "Light",
+            CatchUp::Strong => "Strong",
+        }
+    }
+
+    /// How much the bots max speed changes at most
+    fn speed_strength(&self) -> f32 {
+        match self {
+            CatchUp::Off => 0.0,
+            CatchUp::Light => 0.1,
+            CatchUp::Strong => 0.25,
+        }
+    }
+
+    /// The extra item luck the last placed racer gets
+    fn item_strength(&self) -> f32 {
+        match self {
+            CatchUp::Off => 0.0,
+            CatchUp::Light => 0.3,
+            CatchUp::Strong => 0.6,
+        }
+    }
+}
+
+/// Speeds up bots behind the player and slows down the ones ahead, and optionally gives
+/// trailing racers better item odds
+pub fn catch_up_system(
+    mut racer_query: Query<
+        (
+            &Progress,
+            &Rank,
+            &mut BicycleParams,
+            Has<Player>,
+            Has<WaypointAi>,
+        ),
+        With<Bicycle>,
+    >,
+    waypoint_query: Query<&Waypoint>,
+    track_length: Res<TrackLength>,
+    race_config: Res<RaceConfig>,
+) {
+    let race_distance = |progress: &Progress| {
+        waypoint_query
+            .get(progress.next_checkpoint)
+            .map(|waypoint| progress.race_distance(waypoint, track_length.0))
+            .ok()
+    };
+
+    let player_distance = racer_query
+        .iter()
+        .find(|(_, _, _, player, _)| *player)
+        .and_then(|(progress, ..)| race_distance(progress));
+
+    let racers = racer_query.iter().count();
+
+    for (progress, rank, mut params, _, bot) in racer_query.iter_mut() {
+        params.catch_up_speed = 1.0;
+        params.item_luck = 1.0;
+
+        if bot {
+            if let (Some(player_distance), Some(distance)) =
+                (player_distance, race_distance(progress))
+            {
+                let gap = ((player_distance - distance) / FULL_EFFECT_GAP).clamp(-1.0, 1.0);
+                params.catch_up_speed = 1.0 + gap * race_config.catch_up.speed_strength();
+            }
+        }
+
+        if race_config.catch_up_items && racers > 1 {
+            let trailing = (rank.0 as f32 - 1.0) / (racers as f32 - 1.0);
+            params.item_luck = 1.0 + trailing * race_config.catch_up.item_strength();
+        }
+    }
+}
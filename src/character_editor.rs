@@ -1,3 +1,4 @@
+use crate::bike::BicycleParams;
 use crate::bike_config::{BicycleModTrait, PlayerConfig, Selectable};
 use bevy::prelude::{DetectChangesMut, ResMut};
 use bevy_egui::egui::Ui;
@@ -30,11 +31,29 @@ pub fn character_editor(ui: &mut Ui, mut player: &mut ResMut<PlayerConfig>) {
     ui.label("Starter Item:");
     changed |= select(ui, &mut config.0.bike.addon);
 
+    stats(ui, &config.0.params());
+
     if changed {
         player.set_changed();
     }
 }
 
+/// Shows how the current build handles, the bars are full at roughly the best combination
+fn stats(ui: &mut Ui, params: &BicycleParams) {
+    egui::Grid::new("Stats").show(ui, |ui| {
+        for (name, value, full) in [
+            ("Speed", params.max_speed, 1.5),
+            ("Acceleration", params.acceleration, 2.5),
+            ("Turn", params.turn, 1.5),
+            ("Grip", params.drift, 2.5),
+        ] {
+            ui.label(name);
+            ui.add(egui::ProgressBar::new(value / full).desired_width(130.0));
+            ui.end_row();
+        }
+    });
+}
+
 pub fn select(ui: &mut egui::Ui, item: &mut (impl Selectable + BicycleModTrait)) -> bool {
     let mut changed = false;
     ui.horizontal_top(|ui| {
@@ -77,7 +77,7 @@ pub fn countdown_ui(
                 if let Some(image) = image {
                     Frame::window(ui.style()).show(ui, |ui| {
                         ui.label("Track:");
-                        ui.heading(&race_config.map);
+                        ui.heading(race_config.layout_name());
                     });
                     ui.image(SizedTexture::new(*image, egui::Vec2::new(500.0, 250.0)));
                 }
@@ -0,0 +1,318 @@
+use crate::ai::rivals::{RivalId, ROSTER};
+use crate::ai::skill::AiDifficulty;
+use crate::game_state::{layout_name, GameConfig, GameState, RaceConfig};
+use crate::storage::SaveStorage;
+use crate::tracks::TrackRegistry;
+use bevy::prelude::*;
+use bevy_egui::egui::{Align, Align2, Area, ComboBox, Frame, Id, Layout, RichText};
+use bevy_egui::{egui, EguiContexts};
+use enum_iterator::all;
+use serde::{Deserialize, Serialize};
+
+const CUPS: &str = include_str!("../assets/cups.ron");
+const CUSTOM_CUPS_KEY: &str = "custom_cups";
+
+/// Points for finishing first, second, and so on. Everyone further back gets nothing.
+pub const POINTS: [usize; 8] = [10, 8, 6, 5, 4, 3, 2, 1];
+/// Finishing the cup in one of these positions unlocks the level selector
+const PODIUM_PLACES: usize = 3;
+
+pub fn points_for(rank: usize) -> usize {
+    rank.checked_sub(1)
+        .and_then(|idx| POINTS.get(idx))
+        .copied()
+        .unwrap_or(0)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CupTrack {
+    pub map: String,
+    pub laps: usize,
+    #[serde(default)]
+    pub reversed: bool,
+    #[serde(default)]
+    pub mirrored: bool,
+}
+
+impl CupTrack {
+    pub fn layout_name(&self) -> String {
+        layout_name(&self.map, self.reversed, self.mirrored)
+    }
+
+    /// Sets up the race config to drive this track next
+    pub fn apply(&self, race_config: &mut RaceConfig) {
+        race_config.map = self.map.clone();
+        race_config.laps = self.laps;
+        race_config.reversed = self.reversed;
+        race_config.mirrored = self.mirrored;
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Cup {
+    pub name: String,
+    pub difficulty: AiDifficulty,
+    pub tracks: Vec<CupTrack>,
+}
+
+/// The cups from `assets/cups.ron` and the ones players built themselves
+#[derive(Resource, Debug)]
+pub struct Cups {
+    pub builtin: Vec<Cup>,
+    pub custom: Vec<Cup>,
+}
+
+impl FromWorld for Cups {
+    fn from_world(world: &mut World) -> Self {
+        let storage = world.resource::<SaveStorage>();
+        Self {
+            builtin: ron::from_str(CUPS).expect("assets/cups.ron is invalid"),
+            custom: storage.load(CUSTOM_CUPS_KEY).unwrap_or_default(),
+        }
+    }
+}
+
+impl Cups {
+    pub fn all(&self) -> impl Iterator<Item = &Cup> {
+        self.builtin.iter().chain(self.custom.iter())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Cup> {
+        self.all().find(|cup| cup.name == name)
+    }
+
+    pub fn save_custom(&self, storage: &SaveStorage) {
+        storage.save(CUSTOM_CUPS_KEY, &self.custom);
+    }
+}
+
+/// Which racer of the cup a bike is, the player is slot 0 and the bots follow in order
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CupSlot(pub usize);
+
+/// The cup currently being driven, so the same rivals race every track and collect points
+#[derive(Resource, Debug, Default)]
+pub struct CupSession {
+    pub active: bool,
+    pub cup: Cup,
+    pub rivals: Vec<RivalId>,
+    /// Points of each cup slot
+    pub points: Vec<usize>,
+    /// Number of races that already counted
+    pub races: usize,
+}
+
+impl CupSession {
+    pub fn start(cup: Cup, rivals: Vec<RivalId>) -> Self {
+        Self {
+            active: true,
+            cup,
+            points: vec![0; rivals.len() + 1],
+            rivals,
+            races: 0,
+        }
+    }
+
+    pub fn name(&self, slot: usize) -> &'static str {
+        match slot.checked_sub(1) {
+            Some(bot) => ROSTER[self.rivals[bot].0].name,
+            None => "You",
+        }
+    }
+
+    /// Adds the points for a race, given the rank of each slot
+    pub fn award(&mut self, results: &[(CupSlot, usize)]) {
+        for (slot, rank) in results {
+            if let Some(points) = self.points.get_mut(slot.0) {
+                *points += points_for(*rank);
+            }
+        }
+        self.races += 1;
+    }
+
+    /// Slots and their points sorted by points, including the points `results` would award
+    pub fn standings(&self, results: &[(CupSlot, usize)]) -> Vec<(usize, usize)> {
+        let mut standings: Vec<(usize, usize)> =
+            self.points.iter().copied().enumerate().collect();
+        for (slot, rank) in results {
+            if let Some((_, points)) = standings.get_mut(slot.0) {
+                *points += points_for(*rank);
+            }
+        }
+        standings.sort_by(|(_, a), (_, b)| b.cmp(a));
+        standings
+    }
+
+    /// The track of the race currently being driven
+    pub fn current_track(&self) -> Option<&CupTrack> {
+        self.cup.tracks.get(self.races)
+    }
+
+    /// The track after the current one, `None` if this is the last race
+    pub fn next_track(&self) -> Option<&CupTrack> {
+        self.cup.tracks.get(self.races + 1)
+    }
+
+    /// The overall position of the player, starting at 1
+    pub fn player_position(&self) -> usize {
+        self.standings(&[])
+            .iter()
+            .position(|(slot, _)| *slot == 0)
+            .map_or(self.points.len(), |idx| idx + 1)
+    }
+}
+
+/// Shows the cup standings as a table, with the points of the current race added on top
+pub fn standings_grid(ui: &mut egui::Ui, cup: &CupSession, results: &[(CupSlot, usize)]) {
+    egui::Grid::new("Cup Standings").striped(true).show(ui, |ui| {
+        ui.label("Pos");
+        ui.label("Name");
+        ui.label("Race");
+        ui.label("Points");
+        ui.end_row();
+
+        for (position, (slot, points)) in cup.standings(results).into_iter().enumerate() {
+            let race_points = results
+                .iter()
+                .find(|(cup_slot, _)| cup_slot.0 == slot)
+                .map_or(0, |(_, rank)| points_for(*rank));
+
+            ui.label(format!("{}.", position + 1));
+            ui.label(cup.name(slot));
+            ui.label(format!("+{}", race_points));
+            ui.label(points.to_string());
+            ui.end_row();
+        }
+    });
+}
+
+pub fn finish_cup(cup: Res<CupSession>, mut game_config: ResMut<GameConfig>) {
+    if cup.player_position() <= PODIUM_PLACES && !game_config.level_selector_unlocked {
+        game_config.level_selector_unlocked = true;
+    }
+}
+
+pub fn podium_ui(
+    mut egui: EguiContexts,
+    mut cup: ResMut<CupSession>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    Area::new(Id::new("Podium"))
+        .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(egui.ctx_mut(), |ui| {
+            Frame::window(ui.style()).show(ui, |ui| {
+                ui.with_layout(Layout::top_down_justified(Align::Center), |ui| {
+                    let standings = cup.standings(&[]);
+
+                    if let Some((winner, _)) = standings.first() {
+                        let text = format!("{} won the cup!", cup.name(*winner));
+                        ui.heading(RichText::new(text).size(32.0));
+                    }
+
+                    ui.horizontal(|ui| {
+                        // Second on the left, the winner in the middle and third on the right
+                        for place in [1, 0, 2] {
+                            if let Some((slot, points)) = standings.get(place) {
+                                ui.vertical(|ui| {
+                                    ui.add_space([30.0, 0.0, 50.0][place]);
+                                    ui.heading(format!("{}.", place + 1));
+                                    ui.label(RichText::new(cup.name(*slot)).strong());
+                                    ui.label(format!("{} points", points));
+                                });
+                            }
+                        }
+                    });
+
+                    ui.separator();
+                    standings_grid(ui, &cup, &[]);
+
+                    let position = cup.player_position();
+                    if position <= PODIUM_PLACES {
+                        ui.heading(format!(
+                            "You made the podium in position {} and unlocked the level selector!",
+                            position
+                        ));
+                    } else {
+                        ui.heading(format!(
+                            "You finished the cup in position {}. Make the podium to unlock the level selector.",
+                            position
+                        ));
+                    }
+
+                    if ui.button("Main Menu").clicked() {
+                        cup.active = false;
+                        next_game_state.set(GameState::MainMenu);
+                    }
+                });
+            });
+        });
+}
+
+/// Lets the player put together their own cup from every installed track.
+/// Returns true when the cup should be saved.
+pub fn cup_builder(ui: &mut egui::Ui, draft: &mut Cup, tracks: &TrackRegistry) -> bool {
+    ui.horizontal(|ui| {
+        ui.label("Name:");
+        ui.text_edit_singleline(&mut draft.name);
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Difficulty:");
+        for difficulty in all::<AiDifficulty>() {
+            ui.selectable_value(&mut draft.difficulty, difficulty, difficulty.name());
+        }
+    });
+
+    let mut remove = None;
+    let mut move_up = None;
+    egui::Grid::new("Cup Builder Tracks").show(ui, |ui| {
+        for (idx, track) in draft.tracks.iter_mut().enumerate() {
+            ui.label(format!("{}.", idx + 1));
+            ui.label(&track.map);
+            ui.add(
+                egui::DragValue::new(&mut track.laps)
+                    .range(1..=10)
+                    .suffix(" laps"),
+            );
+            ui.checkbox(&mut track.reversed, "Reverse");
+            ui.checkbox(&mut track.mirrored, "Mirror");
+            if ui.add_enabled(idx > 0, egui::Button::new("⬆")).clicked() {
+                move_up = Some(idx);
+            }
+            if ui.button("✖").clicked() {
+                remove = Some(idx);
+            }
+            ui.end_row();
+        }
+    });
+    if let Some(idx) = move_up {
+        draft.tracks.swap(idx - 1, idx);
+    }
+    if let Some(idx) = remove {
+        draft.tracks.remove(idx);
+    }
+
+    ComboBox::new("Cup Builder Add Track", "")
+        .selected_text("Add Track")
+        .show_ui(ui, |ui| {
+            for track in tracks.of_kind(false) {
+                let label = match &track.pack {
+                    Some(pack) => format!("{} ({})", track.name, pack),
+                    None => track.name.clone(),
+                };
+                if ui.selectable_label(false, label).clicked() {
+                    draft.tracks.push(CupTrack {
+                        map: track.name.clone(),
+                        laps: 3,
+                        reversed: false,
+                        mirrored: false,
+                    });
+                }
+            }
+        });
+
+    let valid = !draft.name.trim().is_empty() && !draft.tracks.is_empty();
+    ui.add_enabled(valid, egui::Button::new("Save Cup"))
+        .on_disabled_hover_text("A cup needs a name and at least one track")
+        .clicked()
+}
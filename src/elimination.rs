@@ -0,0 +1,248 @@
+use crate::ai::rivals::RivalId;
+use crate::bike::{Bicycle, BicycleControl, Player};
+use crate::cup::CupSlot;
+use crate::game_state::{race_running, GameState, RaceConfig, RaceState, RaceType};
+use crate::lap_timer::{lap_timer_system, LapCompletedEvent, LapTimes, RaceClock};
+use crate::ranking::{rank_bicycles_system, FinishTime, Rank};
+use crate::team::Team;
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
+
+/// Seconds a knocked out racer takes to fade away
+const FADE_TIME: f32 = 1.0;
+
+pub struct EliminationPlugin;
+
+impl Plugin for EliminationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Elimination>()
+            .add_systems(OnEnter(GameState::Race), reset_elimination)
+            .add_systems(
+                Update,
+                (
+                    (
+                        elimination_system
+                            .after(lap_timer_system)
+                            .after(rank_bicycles_system),
+                        last_one_standing_system,
+                    )
+                        .chain()
+                        .run_if(race_running),
+                    fade_out_system,
+                )
+                    .run_if(in_state(GameState::Race)),
+            );
+    }
+}
+
+/// When the last placed racer of an elimination race is knocked out
+#[derive(Debug, Clone, Copy, Default, Sequence, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EliminationRule {
+    /// Whenever the leader completes a lap
+    #[default]
+    Lap,
+    /// Every [`RaceConfig::elimination_interval`] seconds
+    Timer,
+}
+
+impl EliminationRule {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EliminationRule::Lap => "Every Lap",
+            EliminationRule::Timer => "Timed",
+        }
+    }
+}
+
+/// A racer that was knocked out, kept after its bike is gone for the results
+#[derive(Debug, Clone)]
+pub struct KnockedOut {
+    pub slot: CupSlot,
+    pub name: String,
+    pub rival: Option<RivalId>,
+    pub player: bool,
+    /// The final placing, the first racer out of five finishes fifth
+    pub place: usize,
+    /// Race clock time of the knockout
+    pub time: f32,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct Elimination {
+    /// Race clock time of the next knockout in timed eliminations
+    pub next_at: f32,
+    /// Laps completed by the leader, every new one knocks out a racer in lap eliminations
+    pub leader_laps: usize,
+    pub knocked_out: Vec<KnockedOut>,
+}
+
+impl Elimination {
+    pub fn player_knocked_out(&self) -> Option<&KnockedOut> {
+        self.knocked_out.iter().find(|knocked_out| knocked_out.player)
+    }
+
+    /// Takes a racer out of the race, it fades away and is despawned
+    pub fn knock_out(&mut self, commands: &mut Commands, entity: Entity, knocked_out: KnockedOut) {
+        commands
+            .entity(entity)
+            .insert((Eliminated, Rank(knocked_out.place), Sensor, FadeOut::default()))
+            .remove::<BicycleControl>();
+        self.knocked_out.push(knocked_out);
+    }
+}
+
+/// Marks a knocked out racer, which no longer takes part in the ranking
+#[derive(Component, Debug)]
+pub struct Eliminated;
+
+/// Fades the sprites of an entity away and despawns it when they are gone
+#[derive(Component, Debug)]
+pub struct FadeOut {
+    timer: Timer,
+}
+
+impl Default for FadeOut {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(FADE_TIME, TimerMode::Once),
+        }
+    }
+}
+
+fn reset_elimination(mut elimination: ResMut<Elimination>, race_config: Res<RaceConfig>) {
+    *elimination = Elimination {
+        next_at: race_config.elimination_interval as f32,
+        ..Default::default()
+    };
+}
+
+pub fn elimination_system(
+    mut commands: Commands,
+    racer_query: Query<
+        (Entity, &Rank, &Name, &CupSlot, Option<&RivalId>, Has<Player>),
+        (With<Bicycle>, Without<Eliminated>, Without<FinishTime>),
+    >,
+    mut lap_events: EventReader<LapCompletedEvent>,
+    mut elimination: ResMut<Elimination>,
+    mut next_race_state: ResMut<NextState<RaceState>>,
+    race_config: Res<RaceConfig>,
+    clock: Res<RaceClock>,
+) {
+    if race_config.race_type != RaceType::Elimination {
+        return;
+    }
+
+    let mut knockout = false;
+    match race_config.elimination {
+        EliminationRule::Lap => {
+            for event in lap_events.read() {
+                if event.lap > elimination.leader_laps {
+                    elimination.leader_laps = event.lap;
+                    knockout = true;
+                }
+            }
+        }
+        EliminationRule::Timer => {
+            if clock.0 >= elimination.next_at {
+                elimination.next_at += race_config.elimination_interval as f32;
+                knockout = true;
+            }
+        }
+    }
+
+    let remaining = racer_query.iter().count();
+    if !knockout || remaining < 2 {
+        return;
+    }
+
+    let last = racer_query.iter().max_by_key(|(_, rank, ..)| rank.0);
+    if let Some((entity, _, name, slot, rival, player)) = last {
+        elimination.knock_out(
+            &mut commands,
+            entity,
+            KnockedOut {
+                slot: *slot,
+                name: name.to_string(),
+                rival: rival.copied(),
+                player,
+                place: remaining,
+                time: clock.0,
+            },
+        );
+
+        if player {
+            next_race_state.set(RaceState::Finished);
+        }
+    }
+}
+
+/// Ends eliminations and battles once only one racer or team is left, who wins
+pub fn last_one_standing_system(
+    mut commands: Commands,
+    racer_query: Query<
+        (Entity, &LapTimes, Has<Player>, Option<&Team>),
+        (With<Bicycle>, Without<Eliminated>, Without<FinishTime>),
+    >,
+    mut next_race_state: ResMut<NextState<RaceState>>,
+    race_config: Res<RaceConfig>,
+    clock: Res<RaceClock>,
+    assets: Res<AssetServer>,
+) {
+    if !matches!(race_config.race_type, RaceType::Elimination | RaceType::Arena) {
+        return;
+    }
+    let Some((_, _, _, first_team)) = racer_query.iter().next() else {
+        return;
+    };
+    let one_left = racer_query.iter().count() == 1
+        || racer_query
+            .iter()
+            .all(|(.., team)| Team::same(first_team, team));
+    if !one_left {
+        return;
+    }
+
+    let mut player_won = false;
+    for (entity, times, player, _) in racer_query.iter() {
+        commands.entity(entity).insert(FinishTime {
+            time: clock.0,
+            best_lap: times.best_lap(),
+        });
+        player_won |= player;
+    }
+
+    if player_won {
+        next_race_state.set(RaceState::Finished);
+
+        commands.spawn(AudioBundle {
+            source: assets.load("sounds/finish.mp3"),
+            settings: PlaybackSettings::DESPAWN,
+        });
+    }
+}
+
+pub fn fade_out_system(
+    mut commands: Commands,
+    mut fade_query: Query<(Entity, &mut FadeOut)>,
+    children_query: Query<&Children>,
+    mut sprite_query: Query<&mut Sprite>,
+    time: Res<Time>,
+) {
+    for (entity, mut fade) in fade_query.iter_mut() {
+        fade.timer.tick(time.delta());
+
+        if fade.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let alpha = fade.timer.fraction_remaining();
+        for child in children_query.iter_descendants(entity) {
+            if let Ok(mut sprite) = sprite_query.get_mut(child) {
+                sprite.color.set_alpha(alpha);
+            }
+        }
+    }
+}
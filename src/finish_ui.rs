@@ -1,6 +1,17 @@
-use crate::bike::{Bicycle, Player};
-use crate::game_state::{GameState, RaceConfig, RaceState, MAPS};
-use crate::ranking::{Progress, Rank};
+use crate::ai::rivals::{RivalId, ROSTER};
+use crate::arena::{Balloons, START_BALLOONS};
+use crate::bike::{Bicycle, DriftTurbo, Player};
+use crate::bike_config::{BicycleModTrait, PlayerConfig};
+use crate::cup::{standings_grid, CupSession, CupSlot};
+use crate::elimination::{Elimination, EliminationRule};
+use crate::game_state::{GameState, RaceConfig, RaceState, RaceType};
+use crate::ghost::BestGhosts;
+use crate::lap_timer::{format_time, LapTimes, RaceClock, Sectors};
+use crate::ranking::{FinishTime, Progress, Rank};
+use crate::records::{NewRecords, Records};
+use crate::rush::Rush;
+use crate::team::team_points;
+use crate::waypoint::{TrackLength, Waypoint};
 use bevy::asset::AssetServer;
 use bevy::log::tracing_subscriber::fmt::format;
 use bevy::prelude::*;
@@ -13,8 +24,28 @@ pub fn finish_ui(
     mut image: Local<Option<egui::TextureId>>,
     mut egui: EguiContexts,
     mut next_game_state: ResMut<NextState<GameState>>,
-    query: Query<(&Bicycle, &Rank, &Name, &Progress), With<Player>>,
+    query: Query<(&Bicycle, &Rank, &Name, &Progress, &LapTimes), With<Player>>,
+    standings_query: Query<
+        (
+            &Rank,
+            &Name,
+            &Progress,
+            Option<&FinishTime>,
+            Option<&RivalId>,
+            &CupSlot,
+        ),
+        With<Bicycle>,
+    >,
+    waypoint_query: Query<&Waypoint>,
     mut race_config: ResMut<RaceConfig>,
+    player_config: Res<PlayerConfig>,
+    track_length: Res<TrackLength>,
+    clock: Res<RaceClock>,
+    new_records: Res<NewRecords>,
+    mut cup: ResMut<CupSession>,
+    elimination: Res<Elimination>,
+    rush: Res<Rush>,
+    records: Res<Records>,
 ) {
     let image = image.get_or_insert_with(|| egui.add_image(assets.load("things/Banner.webp")));
 
@@ -28,28 +59,236 @@ pub fn finish_ui(
                 });
 
                 ui.with_layout(Layout::top_down_justified(Align::Center), |ui| {
-                    if let Some((_, rank, ..)) = query.iter().next() {
-                        let text = match rank.0 {
-                            1 => "You won!".to_string(),
-                            2 => "You came in second!".to_string(),
-                            3 => "You came in third!".to_string(),
-                            _ => format!("You finished in position {}", rank.0),
+                    if new_records.any() {
+                        let mut broken = Vec::new();
+                        if new_records.race {
+                            broken.push("race time");
+                        }
+                        if new_records.lap {
+                            broken.push("lap");
+                        }
+                        if new_records.cup {
+                            broken.push("cup result");
+                        }
+                        if new_records.rush {
+                            broken.push("distance");
+                        }
+                        ui.heading(
+                            egui::RichText::new(format!("New record! Best {}", broken.join(", ")))
+                                .color(egui::Color32::GOLD),
+                        );
+                    }
+
+                    if race_config.race_type == RaceType::CheckpointRush {
+                        ui.heading("Time's up!");
+
+                        egui::Grid::new("Rush Score").show(ui, |ui| {
+                            ui.label("Laps");
+                            ui.label(rush.laps.to_string());
+                            ui.end_row();
+                            ui.label("Distance");
+                            ui.label(format!("{:.0} m", rush.distance));
+                            ui.end_row();
+
+                            let best = records
+                                .get(&race_config)
+                                .and_then(|track| track.best_rush.as_ref());
+                            if let Some(best) = best {
+                                ui.label("High Score");
+                                ui.label(format!("{:.0} m ({} laps)", best.distance, best.laps));
+                                ui.end_row();
+                            }
+                        });
+                    } else if race_config.race_type == RaceType::TimeTrial {
+                        if let Some((.., times)) = query.iter().next() {
+                            if let Some(best) = times.best_lap() {
+                                ui.heading(format!("Best lap: {}", format_time(best)));
+                            }
+
+                            egui::Grid::new("Lap Times").show(ui, |ui| {
+                                for (lap, time) in times.laps.iter().enumerate() {
+                                    ui.label(format!("Lap {}", lap + 1));
+                                    ui.label(format_time(*time));
+                                    ui.end_row();
+                                }
+                            });
+                        }
+                    } else if !race_config.race_type.has_laps() {
+                        match elimination.player_knocked_out() {
+                            Some(knocked_out) => ui.heading(format!(
+                                "You were knocked out in position {}",
+                                knocked_out.place
+                            )),
+                            None => ui.heading("You are the last one standing!"),
                         };
 
-                        ui.heading(text);
+                        egui::Grid::new("Standings").striped(true).show(ui, |ui| {
+                            ui.label("Pos");
+                            ui.label("Name");
+                            ui.label("Character");
+                            ui.label("Out At");
+                            ui.end_row();
+
+                            let skin = |rival: Option<&RivalId>| match rival {
+                                Some(rival) => &ROSTER[rival.0].skin,
+                                None => &player_config.0.skin,
+                            };
+
+                            // Racers still in the race, knocked out ones are shown below
+                            for (rank, name, _, _, rival, slot) in standings_query
+                                .iter()
+                                .sort_by_key::<&Rank, _>(|rank| rank.0)
+                            {
+                                if elimination.knocked_out.iter().any(|out| out.slot == *slot) {
+                                    continue;
+                                }
+                                ui.label(format!("{}.", rank.0));
+                                ui.label(name.as_str());
+                                ui.label(skin(rival).name());
+                                ui.label("-");
+                                ui.end_row();
+                            }
+
+                            for knocked_out in elimination.knocked_out.iter().rev() {
+                                ui.label(format!("{}.", knocked_out.place));
+                                ui.label(&knocked_out.name);
+                                ui.label(skin(knocked_out.rival.as_ref()).name());
+                                ui.label(format_time(knocked_out.time));
+                                ui.end_row();
+                            }
+                        });
+                    } else {
+                        if let Some((_, rank, ..)) = query.iter().next() {
+                            let text = match rank.0 {
+                                1 => "You won!".to_string(),
+                                2 => "You came in second!".to_string(),
+                                3 => "You came in third!".to_string(),
+                                _ => format!("You finished in position {}", rank.0),
+                            };
+
+                            ui.heading(text);
+                        }
+
+                        let winner_time = standings_query
+                            .iter()
+                            .find(|(rank, ..)| rank.0 == 1)
+                            .and_then(|(_, _, _, finish, ..)| finish)
+                            .map(|finish| finish.time);
+
+                        egui::Grid::new("Standings").striped(true).show(ui, |ui| {
+                            ui.label("Pos");
+                            ui.label("Name");
+                            ui.label("Character");
+                            ui.label("Time");
+                            ui.label("Best Lap");
+                            ui.label("Gap");
+                            ui.end_row();
+
+                            for (rank, name, progress, finish, rival, _) in standings_query
+                                .iter()
+                                .sort_by_key::<&Rank, _>(|rank| rank.0)
+                            {
+                                let skin = match rival {
+                                    Some(rival) => &ROSTER[rival.0].skin,
+                                    None => &player_config.0.skin,
+                                };
+
+                                // Racers still on track get a time projected from their pace so far
+                                let time = finish.map(|finish| finish.time).or_else(|| {
+                                    let waypoint = waypoint_query.get(progress.next_checkpoint).ok()?;
+                                    progress.projected_finish_time(
+                                        waypoint,
+                                        track_length.0,
+                                        race_config.laps,
+                                        &clock,
+                                    )
+                                });
+                                let projected = if finish.is_none() { "~" } else { "" };
+
+                                ui.label(format!("{}.", rank.0));
+                                ui.label(name.as_str());
+                                ui.label(skin.name());
+                                ui.label(time.map_or("-".to_string(), |time| {
+                                    format!("{}{}", projected, format_time(time))
+                                }));
+                                ui.label(
+                                    finish
+                                        .and_then(|finish| finish.best_lap)
+                                        .map_or("-".to_string(), format_time),
+                                );
+                                match (time, winner_time) {
+                                    (Some(time), Some(winner_time)) if rank.0 > 1 => {
+                                        ui.label(format!("+{:.3}", time - winner_time));
+                                    }
+                                    _ => {
+                                        ui.label("");
+                                    }
+                                }
+                                ui.end_row();
+                            }
+                        });
                     }
 
-                    let map_idx = MAPS.iter().position(|map| map == &race_config.map).unwrap();
-                    if race_config.is_cup {
-                        if let Some(next_map) = MAPS.get(map_idx + 1) {
+                    // Knocked out racers might already be gone, so their placing comes from
+                    // the elimination results
+                    let mut results: Vec<(CupSlot, usize)> = elimination
+                        .knocked_out
+                        .iter()
+                        .map(|knocked_out| (knocked_out.slot, knocked_out.place))
+                        .collect();
+                    for (rank, .., slot) in standings_query.iter() {
+                        if !results.iter().any(|(result_slot, _)| result_slot == slot) {
+                            results.push((*slot, rank.0));
+                        }
+                    }
+
+                    if let Some(player_team) = race_config.team_of(CupSlot(0)) {
+                        let standings = team_points(&race_config, &results);
+                        let best = standings[0].1;
+
+                        ui.separator();
+                        if standings.contains(&(player_team, best)) {
+                            ui.heading("Your team won!");
+                        } else {
+                            ui.heading(format!("{} team won", standings[0].0.name()));
+                        }
+
+                        egui::Grid::new("Team Points").striped(true).show(ui, |ui| {
+                            ui.label("Team");
+                            ui.label("Points");
+                            ui.end_row();
+
+                            for (team, points) in &standings {
+                                let [r, g, b, _] = team.color().to_srgba().to_u8_array();
+                                ui.label(
+                                    egui::RichText::new(team.name())
+                                        .color(egui::Color32::from_rgb(r, g, b)),
+                                );
+                                ui.label(points.to_string());
+                                ui.end_row();
+                            }
+                        });
+                    }
+
+                    if race_config.plays_cup() && cup.active {
+                        ui.separator();
+                        ui.heading(format!(
+                            "{} standings after race {}/{}",
+                            cup.cup.name,
+                            cup.races + 1,
+                            cup.cup.tracks.len()
+                        ));
+                        standings_grid(ui, &cup, &results);
+
+                        if let Some(next_track) = cup.next_track().cloned() {
                             if ui.button("Next Map").clicked() {
+                                cup.award(&results);
                                 next_game_state.set(GameState::Restart);
-                                race_config.map = next_map.to_string();
+                                next_track.apply(&mut race_config);
                             }
-                        } else {
-                            ui.heading(
-                                "Yay! You finished the cup and unlocked the level selector!",
-                            );
+                        } else if ui.button("Cup Results").clicked() {
+                            cup.award(&results);
+                            next_game_state.set(GameState::CupPodium);
                         }
                     }
                     if ui.button("Restart").clicked() {
@@ -66,22 +305,111 @@ pub fn finish_ui(
 
 pub fn lap_ui(
     mut egui: EguiContexts,
-    player_progress_query: Query<(&Player, &Progress, &Rank)>,
+    player_progress_query: Query<(
+        &Player,
+        &Progress,
+        &Rank,
+        &LapTimes,
+        Option<&Balloons>,
+        Option<&DriftTurbo>,
+    )>,
     race_config: Res<RaceConfig>,
+    best_ghosts: Res<BestGhosts>,
+    sectors: Res<Sectors>,
+    clock: Res<RaceClock>,
+    elimination: Res<Elimination>,
+    rush: Res<Rush>,
 ) {
-    if let Some((player, progress, rank)) = player_progress_query.iter().next() {
+    if let Some((player, progress, rank, times, balloons, turbo)) =
+        player_progress_query.iter().next()
+    {
         egui::Area::new(Id::new("Laps"))
             .anchor(egui::Align2::RIGHT_TOP, [0.0, 0.0])
             .show(egui.ctx_mut(), |ui| {
                 ui.style_mut().visuals = Visuals::dark();
                 Frame::window(ui.style()).show(ui, |ui| {
                     ui.style_mut().visuals.override_text_color = Some(egui::Color32::WHITE);
-                    ui.label("Pos:");
-                    ui.heading(format!("{}", rank.0));
+                    if race_config.bot_count() > 0 {
+                        ui.label("Pos:");
+                        ui.heading(format!("{}", rank.0));
+                    }
+                    if let Some(tier) = turbo.map(DriftTurbo::tier).filter(|tier| *tier > 0) {
+                        ui.label("Mini-Turbo:");
+                        ui.heading(
+                            egui::RichText::new("▶".repeat(tier)).color(egui::Color32::GOLD),
+                        );
+                    }
+                    if let Some(balloons) = balloons {
+                        // Battles have no laps or times to show
+                        ui.label("Balloons:");
+                        ui.heading(format!("{}/{}", balloons.left, START_BALLOONS));
+                        return;
+                    }
                     ui.label("Lap:");
-                    ui.heading(format!("{}/{}", progress.round, race_config.laps));
-                    ui.label("Checkpoint:");
-                    ui.heading(format!("{}", progress.checkpoint_idx));
+                    if race_config.race_type.has_laps() {
+                        ui.heading(format!("{}/{}", progress.round, race_config.laps));
+                    } else {
+                        ui.heading(format!("{}", progress.round));
+                    }
+                    if race_config.race_type == RaceType::Elimination {
+                        ui.label("Next Out:");
+                        match race_config.elimination {
+                            EliminationRule::Lap => ui.heading(format!(
+                                "End of lap {}",
+                                elimination.leader_laps + 1
+                            )),
+                            EliminationRule::Timer => ui.heading(format!(
+                                "{:.0}s",
+                                (elimination.next_at - clock.0).max(0.0).ceil()
+                            )),
+                        };
+                    }
+                    if race_config.race_type == RaceType::CheckpointRush {
+                        let color = if rush.time_left < 5.0 {
+                            egui::Color32::RED
+                        } else {
+                            egui::Color32::WHITE
+                        };
+                        ui.label("Time Left:");
+                        ui.heading(
+                            egui::RichText::new(format!("{:.1}s", rush.time_left)).color(color),
+                        );
+                        if let Some(bonus) = rush.last_bonus {
+                            ui.heading(
+                                egui::RichText::new(format!("+{:.1}s", bonus))
+                                    .color(egui::Color32::GREEN),
+                            );
+                        }
+                        ui.label("Distance:");
+                        ui.heading(format!("{:.0} m", rush.distance));
+                    }
+                    ui.label("Sector:");
+                    ui.heading(format!("{}/{}", times.splits.len() + 1, sectors.count()));
+                    if times.started() {
+                        ui.label("Time:");
+                        ui.heading(format_time(times.current_lap(&clock)));
+                    }
+                    if let Some(delta) = times.last_delta {
+                        let color = if delta <= 0.0 {
+                            egui::Color32::GREEN
+                        } else {
+                            egui::Color32::RED
+                        };
+                        ui.label("Split:");
+                        ui.heading(egui::RichText::new(format!("{:+.3}", delta)).color(color));
+                    }
+                    if let Some(last) = times.last_lap() {
+                        ui.label("Last:");
+                        ui.heading(format_time(last));
+                    }
+                    if let Some(best) = times.best_lap() {
+                        ui.label("Best:");
+                        ui.heading(format_time(best));
+                    }
+                    if let Some(ghost) = best_ghosts.0.get(&race_config.layout_name()) {
+                        ui.label("Ghost:");
+                        ui.heading(format_time(ghost.time));
+                    }
                 });
             });
     }
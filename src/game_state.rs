@@ -1,4 +1,11 @@
+use crate::ai::skill::AiDifficulty;
+use crate::catch_up::CatchUp;
+use crate::cup::CupSlot;
+use crate::elimination::EliminationRule;
+use crate::team::{Team, MAX_TEAMS};
 use bevy::prelude::*;
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Component)]
 pub struct DespawnMe;
@@ -9,6 +16,8 @@ pub enum GameState {
     Race,
     // Utility to run race setup again
     Restart,
+    /// The final standings after the last race of a cup
+    CupPodium,
 }
 
 #[derive(Debug, States, Clone, Eq, PartialEq, Hash, Default)]
@@ -20,12 +29,90 @@ pub enum RaceState {
     Finished,
 }
 
-#[derive(Debug, Resource)]
+#[derive(Debug, Clone, Copy, Default, Sequence, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RaceType {
+    #[default]
+    Race,
+    /// Alone against the clock and the ghost of the best lap, without bots or items
+    TimeTrial,
+    /// The last placed racer is knocked out until only one is left
+    Elimination,
+    /// Item battle on a closed arena map, the last racer with balloons left wins
+    Arena,
+    /// Against a running clock that checkpoints add time to, the run ends when it runs out
+    CheckpointRush,
+}
+
+impl RaceType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RaceType::Race => "Race",
+            RaceType::TimeTrial => "Time Trial",
+            RaceType::Elimination => "Elimination",
+            RaceType::Arena => "Battle Arena",
+            RaceType::CheckpointRush => "Checkpoint Rush",
+        }
+    }
+
+    /// Whether the race ends after a number of laps, elimination races go on until one is left
+    pub fn has_laps(&self) -> bool {
+        !matches!(
+            self,
+            RaceType::Elimination | RaceType::Arena | RaceType::CheckpointRush
+        )
+    }
+
+    /// Whether the race is driven on a track with waypoints, otherwise it's on an arena map
+    pub fn has_track(&self) -> bool {
+        *self != RaceType::Arena
+    }
+
+    pub fn has_bots(&self) -> bool {
+        *self != RaceType::TimeTrial
+    }
+
+    pub fn has_items(&self) -> bool {
+        *self != RaceType::TimeTrial
+    }
+
+    /// Rush runs are about the clock, the bots are only traffic
+    pub fn has_teams(&self) -> bool {
+        self.has_bots() && *self != RaceType::CheckpointRush
+    }
+
+    /// Time trials are for practising, battles and rush runs aren't part of cups
+    pub fn has_cups(&self) -> bool {
+        matches!(self, RaceType::Race | RaceType::Elimination)
+    }
+}
+
+#[derive(Debug, Resource, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct RaceConfig {
+    pub race_type: RaceType,
     pub ai_count: usize,
     pub map: String,
+    /// Drive the track the other way around
+    pub reversed: bool,
+    /// Flip the whole map horizontally
+    pub mirrored: bool,
     pub laps: usize,
+    /// Whether a cup is picked over a single race, only for race types with cups, see
+    /// [`RaceConfig::plays_cup`]
     pub is_cup: bool,
+    /// Name of the selected cup, see [`crate::cup::Cups`]
+    pub cup: String,
+    pub difficulty: AiDifficulty,
+    pub catch_up: CatchUp,
+    /// Gives racers further back better items, independent of the bot speed adjustment
+    pub catch_up_items: bool,
+    pub elimination: EliminationRule,
+    /// Seconds between knockouts in timed eliminations
+    pub elimination_interval: u32,
+    /// Bots drive along in checkpoint rush runs, getting in the way
+    pub rush_traffic: bool,
+    /// Number of teams the racers are split into, below 2 everyone races for themselves
+    pub teams: usize,
 }
 
 #[derive(Debug, Resource)]
@@ -41,24 +128,85 @@ impl Default for GameConfig {
     }
 }
 
-pub const MAPS: [&str; 3] = ["Pool", "Uphill Both Ways", "Milky Way"];
-pub const MAP_DATA: [&str; 3] = [
+pub const MAPS: [&str; 4] = ["Pool", "Uphill Both Ways", "Milky Way", "Pool Arena"];
+pub const MAP_DATA: [&str; 4] = [
     include_str!("../assets/maps/Pool.svg"),
     include_str!("../assets/maps/Uphill Both Ways.svg"),
     include_str!("../assets/maps/Milky Way.svg"),
+    include_str!("../assets/maps/Pool Arena.svg"),
 ];
 
 impl Default for RaceConfig {
     fn default() -> Self {
         Self {
+            race_type: RaceType::default(),
             ai_count: 4,
             map: MAPS[0].to_string(),
+            reversed: false,
+            mirrored: false,
             laps: 3,
             is_cup: true,
+            cup: String::new(),
+            difficulty: AiDifficulty::default(),
+            catch_up: CatchUp::default(),
+            catch_up_items: false,
+            elimination: EliminationRule::default(),
+            elimination_interval: 30,
+            rush_traffic: false,
+            teams: 0,
         }
     }
 }
 
+/// The name of a map including its variant, like `Pool (Reverse, Mirror)`
+pub fn layout_name(map: &str, reversed: bool, mirrored: bool) -> String {
+    match (reversed, mirrored) {
+        (false, false) => map.to_string(),
+        (true, false) => format!("{} (Reverse)", map),
+        (false, true) => format!("{} (Mirror)", map),
+        (true, true) => format!("{} (Reverse, Mirror)", map),
+    }
+}
+
+impl RaceConfig {
+    pub fn layout_name(&self) -> String {
+        layout_name(&self.map, self.reversed, self.mirrored)
+    }
+
+    /// Whether the next race starts a cup rather than a single race
+    pub fn plays_cup(&self) -> bool {
+        self.is_cup && self.race_type.has_cups()
+    }
+
+    /// The team of the racer in `slot`, `None` when everyone races for themselves
+    pub fn team_of(&self, slot: CupSlot) -> Option<Team> {
+        let teams = self.teams.min(MAX_TEAMS);
+        (self.race_type.has_teams() && teams >= 2).then(|| Team(slot.0 % teams))
+    }
+
+    /// The number of bots that actually race, which depends on the race type
+    pub fn bot_count(&self) -> usize {
+        if self.race_type == RaceType::CheckpointRush && !self.rush_traffic {
+            0
+        } else if self.race_type.has_bots() {
+            self.ai_count
+        } else {
+            0
+        }
+    }
+}
+
+/// Run condition for everything that keeps the race going, which continues after the player
+/// finished so the others can finish too
+pub fn race_running(race_state: Res<State<RaceState>>) -> bool {
+    matches!(**race_state, RaceState::Playing | RaceState::Finished)
+}
+
+/// Run condition for the systems that follow the racers along the track waypoints
+pub fn on_track(race_config: Res<RaceConfig>) -> bool {
+    race_config.race_type.has_track()
+}
+
 pub fn despawn_all(mut commands: Commands, query: Query<(Entity), With<DespawnMe>>) {
     for entity in query.iter() {
         commands.entity(entity).despawn_recursive();
@@ -0,0 +1,255 @@
+use crate::bike::{spawn_bikes, spawn_selectable, Player};
+use crate::bike_config::{BicycleMod, PlayerConfig};
+use crate::game_state::{DespawnMe, GameState, RaceConfig, RaceState, RaceType};
+use crate::lap_timer::{lap_timer_system, LapCompletedEvent, LapTimes, RaceClock};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+/// How opaque the ghost bike is drawn
+const GHOST_ALPHA: f32 = 0.4;
+/// Seconds between two recorded positions, the ghost interpolates in between
+const SAMPLE_INTERVAL: f32 = 0.05;
+
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BestGhosts>()
+            .add_systems(OnEnter(GameState::Race), spawn_ghost.after(spawn_bikes))
+            .add_systems(
+                Update,
+                (
+                    record_ghost_system
+                        .after(lap_timer_system)
+                        .run_if(in_state(RaceState::Playing)),
+                    (playback_ghost_system, fade_ghost_system),
+                )
+                    .run_if(in_state(GameState::Race)),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct GhostSample {
+    /// Seconds into the lap
+    time: f32,
+    position: Vec2,
+    rotation: Quat,
+}
+
+/// A recorded lap that the ghost replays
+#[derive(Debug, Clone, Default)]
+pub struct GhostLap {
+    pub time: f32,
+    samples: Vec<GhostSample>,
+}
+
+impl GhostLap {
+    /// Where the ghost was `time` seconds into the lap
+    fn sample(&self, time: f32) -> Option<(Vec2, Quat)> {
+        let next = self.samples.partition_point(|sample| sample.time < time);
+        let after = self.samples.get(next)?;
+        let Some(before) = next.checked_sub(1).map(|idx| self.samples[idx]) else {
+            return Some((after.position, after.rotation));
+        };
+
+        let t = ((time - before.time) / (after.time - before.time).max(f32::EPSILON))
+            .clamp(0.0, 1.0);
+        Some((
+            before.position.lerp(after.position, t),
+            before.rotation.slerp(after.rotation, t),
+        ))
+    }
+}
+
+/// The best lap on each track, kept for the whole session
+#[derive(Resource, Debug, Default)]
+pub struct BestGhosts(pub HashMap<String, GhostLap>);
+
+/// Records the player's current lap
+#[derive(Component, Debug, Default)]
+pub struct GhostRecorder {
+    samples: Vec<GhostSample>,
+}
+
+#[derive(Component, Debug)]
+pub struct Ghost;
+
+/// Spawns a see-through copy of the player's bike in time trials, without the addon
+pub fn spawn_ghost(
+    mut commands: Commands,
+    race_config: Res<RaceConfig>,
+    player_config: Res<PlayerConfig>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    assets: Res<AssetServer>,
+) {
+    if race_config.race_type != RaceType::TimeTrial {
+        return;
+    }
+    let Ok((player, transform)) = player_query.get_single() else {
+        return;
+    };
+
+    commands.entity(player).insert(GhostRecorder::default());
+
+    let ghost = commands
+        .spawn((
+            Name::new("Ghost"),
+            Ghost,
+            TransformBundle {
+                local: *transform,
+                ..Default::default()
+            },
+            VisibilityBundle {
+                visibility: Visibility::Hidden,
+                ..Default::default()
+            },
+            DespawnMe,
+        ))
+        .id();
+
+    let mut container_commands = commands.spawn((
+        TransformBundle {
+            local: Transform::from_translation(Vec3::new(-0.9, 0.0, 0.0)),
+            ..Default::default()
+        },
+        VisibilityBundle::default(),
+    ));
+    container_commands.set_parent(ghost);
+
+    let config = &player_config.0;
+    spawn_selectable(
+        ghost,
+        &mut container_commands,
+        &config.bike.frame,
+        &assets,
+        BicycleMod::Frame,
+        false,
+    );
+    spawn_selectable(
+        ghost,
+        &mut container_commands,
+        &config.bike.rear_wheel,
+        &assets,
+        BicycleMod::RearWheel,
+        false,
+    );
+    spawn_selectable(
+        ghost,
+        &mut container_commands,
+        &config.skin,
+        &assets,
+        BicycleMod::Skin,
+        false,
+    );
+    spawn_selectable(
+        ghost,
+        &mut container_commands,
+        &config.hat,
+        &assets,
+        BicycleMod::Hat,
+        false,
+    );
+}
+
+/// Samples the player's lap and keeps it as the new ghost when it beats the best lap
+pub fn record_ghost_system(
+    mut recorder_query: Query<(Entity, &Transform, &LapTimes, &mut GhostRecorder)>,
+    mut lap_events: EventReader<LapCompletedEvent>,
+    mut best_ghosts: ResMut<BestGhosts>,
+    race_config: Res<RaceConfig>,
+    clock: Res<RaceClock>,
+) {
+    for event in lap_events.read() {
+        let Ok((_, transform, _, mut recorder)) = recorder_query.get_mut(event.bike) else {
+            continue;
+        };
+
+        recorder.samples.push(GhostSample {
+            time: event.time,
+            position: transform.translation.xy(),
+            rotation: transform.rotation,
+        });
+        let samples = std::mem::take(&mut recorder.samples);
+
+        let best = best_ghosts.0.get(&race_config.layout_name());
+        if best.map_or(true, |best| event.time < best.time) {
+            best_ghosts.0.insert(
+                race_config.layout_name(),
+                GhostLap {
+                    time: event.time,
+                    samples,
+                },
+            );
+        }
+    }
+
+    for (_, transform, times, mut recorder) in recorder_query.iter_mut() {
+        if !times.started() {
+            continue;
+        }
+
+        let time = times.current_lap(&clock);
+        if recorder
+            .samples
+            .last()
+            .is_some_and(|last| time - last.time < SAMPLE_INTERVAL)
+        {
+            continue;
+        }
+
+        recorder.samples.push(GhostSample {
+            time,
+            position: transform.translation.xy(),
+            rotation: transform.rotation,
+        });
+    }
+}
+
+/// Moves the ghost along the best lap, in sync with the player's current lap
+pub fn playback_ghost_system(
+    mut ghost_query: Query<(&mut Transform, &mut Visibility), With<Ghost>>,
+    player_query: Query<&LapTimes, With<Player>>,
+    best_ghosts: Res<BestGhosts>,
+    race_config: Res<RaceConfig>,
+    clock: Res<RaceClock>,
+) {
+    let Ok(times) = player_query.get_single() else {
+        return;
+    };
+
+    for (mut transform, mut visibility) in ghost_query.iter_mut() {
+        let sample = best_ghosts
+            .0
+            .get(&race_config.layout_name())
+            .filter(|_| times.started())
+            .and_then(|best| best.sample(times.current_lap(&clock)));
+
+        match sample {
+            Some((position, rotation)) => {
+                transform.translation = position.extend(transform.translation.z);
+                transform.rotation = rotation;
+                *visibility = Visibility::Inherited;
+            }
+            None => {
+                *visibility = Visibility::Hidden;
+            }
+        }
+    }
+}
+
+pub fn fade_ghost_system(
+    ghost_query: Query<Entity, With<Ghost>>,
+    children_query: Query<&Children>,
+    mut sprite_query: Query<&mut Sprite>,
+) {
+    for ghost in ghost_query.iter() {
+        for child in children_query.iter_descendants(ghost) {
+            if let Ok(mut sprite) = sprite_query.get_mut(child) {
+                if sprite.color.alpha() != GHOST_ALPHA {
+                    sprite.color.set_alpha(GHOST_ALPHA);
+                }
+            }
+        }
+    }
+}
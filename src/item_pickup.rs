@@ -1,4 +1,4 @@
-use crate::bike::{spawn_selectable, Bicycle, ModContainer};
+use crate::bike::{spawn_selectable, Bicycle, BicycleParams, ModContainer};
 use crate::bike_config::addon::{Addon, AddonComponent};
 use crate::bike_config::{BicycleMod, BicycleModTrait};
 use avian2d::prelude::Collision;
@@ -7,6 +7,9 @@ use enum_iterator::all;
 use rand::prelude::IteratorRandom;
 use rand::{random, thread_rng};
 
+/// Items handed out instead of the rolled one to racers whose item luck is above 1
+const CATCH_UP_ITEMS: [Addon; 2] = [Addon::Rocket, Addon::Hook];
+
 pub struct ItemPickupPlugin;
 
 impl Plugin for ItemPickupPlugin {
@@ -23,7 +26,14 @@ pub struct ItemPickup {
 
 impl Default for ItemPickup {
     fn default() -> Self {
-        let mut timer = Timer::from_seconds(1.0, TimerMode::Once);
+        Self::with_respawn(1.0)
+    }
+}
+
+impl ItemPickup {
+    /// A pickup that comes back `seconds` after it was taken
+    pub fn with_respawn(seconds: f32) -> Self {
+        let mut timer = Timer::from_seconds(seconds, TimerMode::Once);
         Self { item: None, timer }
     }
 }
@@ -33,7 +43,7 @@ pub fn item_collision_system(
     mut collisions: EventReader<Collision>,
     mut pickup_query: Query<(Entity, &mut ItemPickup, &mut Visibility)>,
     mut bicycle_query: Query<
-        (Entity, &Children),
+        (Entity, &Children, Option<&BicycleParams>),
         (Without<ItemPickup>, With<Bicycle>, Without<ModContainer>),
     >,
     mut addon_container_query: Query<&Children, With<ModContainer>>,
@@ -45,7 +55,7 @@ pub fn item_collision_system(
             .iter_many_mut([contact.entity1, contact.entity2])
             .fetch_next()
         {
-            if let Some((bicycle_entity, bicycle_children)) = bicycle_query
+            if let Some((bicycle_entity, bicycle_children, params)) = bicycle_query
                 .iter_many_mut([contact.entity1, contact.entity2])
                 .fetch_next()
             {
@@ -67,6 +77,13 @@ pub fn item_collision_system(
                     continue;
                 }
 
+                let luck = params.map_or(1.0, |params| params.item_luck);
+                let item = if random::<f32>() < luck - 1.0 {
+                    CATCH_UP_ITEMS.iter().choose(&mut thread_rng()).cloned()
+                } else {
+                    item
+                };
+
                 if let Some(item) = item {
                     spawn_selectable(
                         bicycle_entity,
@@ -0,0 +1,132 @@
+use crate::ranking::Progress;
+use bevy::prelude::*;
+
+/// Seconds since the start of the race, only running while the race is played
+#[derive(Resource, Debug, Default)]
+pub struct RaceClock(pub f32);
+
+/// Waypoint indices at which a sector ends. The last sector always ends at the start line.
+#[derive(Resource, Debug, Default)]
+pub struct Sectors(pub Vec<usize>);
+
+impl Sectors {
+    /// Splits a track with `waypoints` waypoints into thirds
+    pub fn thirds(waypoints: usize) -> Self {
+        Self(vec![waypoints / 3, waypoints * 2 / 3])
+    }
+
+    pub fn count(&self) -> usize {
+        self.0.len() + 1
+    }
+}
+
+/// Sent whenever a racer crosses the start line after a full lap
+#[derive(Event, Debug)]
+pub struct LapCompletedEvent {
+    pub bike: Entity,
+    /// The lap that was completed, starting at 1
+    pub lap: usize,
+    pub time: f32,
+}
+
+/// Lap and split times of a racer, all in seconds
+#[derive(Component, Debug, Default)]
+pub struct LapTimes {
+    /// Times of the completed laps
+    pub laps: Vec<f32>,
+    /// Time into the current lap at which each sector of it was completed
+    pub splits: Vec<f32>,
+    /// The splits of the best lap, the last one being the lap time
+    pub best_splits: Vec<f32>,
+    /// Difference of the last split to the same split of the best lap, negative is faster
+    pub last_delta: Option<f32>,
+    /// Race clock time at which the current lap started
+    pub lap_start: f32,
+    round: usize,
+    checkpoint_idx: usize,
+}
+
+impl LapTimes {
+    pub fn current_lap(&self, clock: &RaceClock) -> f32 {
+        clock.0 - self.lap_start
+    }
+
+    pub fn best_lap(&self) -> Option<f32> {
+        self.laps.iter().copied().reduce(f32::min)
+    }
+
+    pub fn last_lap(&self) -> Option<f32> {
+        self.laps.last().copied()
+    }
+
+    /// Adds a split to the current lap and compares it to the best lap
+    fn split(&mut self, time: f32) {
+        self.last_delta = self
+            .best_splits
+            .get(self.splits.len())
+            .map(|best| time - best);
+        self.splits.push(time);
+    }
+
+    /// Whether the racer crossed the start line yet, before that there is no lap to time
+    pub fn started(&self) -> bool {
+        self.round > 0
+    }
+}
+
+/// Formats seconds as `m:ss.mmm`
+pub fn format_time(seconds: f32) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u32;
+    format!(
+        "{}:{:02}.{:03}",
+        millis / 60_000,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+pub fn reset_race_clock(mut clock: ResMut<RaceClock>) {
+    clock.0 = 0.0;
+}
+
+pub fn tick_race_clock(mut clock: ResMut<RaceClock>, time: Res<Time>) {
+    clock.0 += time.delta_seconds();
+}
+
+/// Records a split whenever a sector is completed and a lap time whenever a new round starts
+pub fn lap_timer_system(
+    mut query: Query<(Entity, &Progress, &mut LapTimes)>,
+    clock: Res<RaceClock>,
+    sectors: Res<Sectors>,
+    mut lap_events: EventWriter<LapCompletedEvent>,
+) {
+    for (bike, progress, mut times) in query.iter_mut() {
+        if progress.round != times.round {
+            if times.started() {
+                let time = times.current_lap(&clock);
+                times.split(time);
+                if times.best_lap().map_or(true, |best| time < best) {
+                    times.best_splits = times.splits.clone();
+                }
+                times.laps.push(time);
+                lap_events.send(LapCompletedEvent {
+                    bike,
+                    lap: times.round,
+                    time,
+                });
+            }
+
+            times.round = progress.round;
+            times.lap_start = clock.0;
+            times.splits.clear();
+        } else if progress.checkpoint_idx != times.checkpoint_idx
+            && times.started()
+            && sectors.0.contains(&progress.checkpoint_idx)
+        {
+            let split = times.current_lap(&clock);
+            times.split(split);
+        }
+
+        times.checkpoint_idx = progress.checkpoint_idx;
+    }
+}
@@ -5,32 +5,65 @@
 #![allow(clippy::too_many_arguments, clippy::type_complexity)]
 
 mod addons;
+mod ai;
+mod arena;
 mod bike;
 mod bike_config;
+mod bump;
 mod camera;
+mod catch_up;
 mod character_editor;
 mod countdown;
+mod cup;
+mod elimination;
 mod finish_ui;
 mod game_state;
+mod ghost;
 mod item_pickup;
+mod lap_timer;
 mod main_menu;
 mod map;
+mod name_tag;
 mod ranking;
+mod records;
+mod rush;
+mod save;
+mod slipstream;
 mod slow;
+mod storage;
+mod team;
+mod tracks;
 mod waypoint;
 
 use crate::addons::giraffe::GiraffePlugin;
 use crate::addons::hook::HookPlugin;
 use crate::addons::lasso::{FireLassoEvent, LassoPlugin};
 use crate::addons::rocket;
+use crate::arena::ArenaPlugin;
+use crate::bump::BumpPlugin;
+use crate::ai::racing_line::{build_racing_line, RacingLine};
+use crate::ai::skill::AiSkill;
+use crate::ai::stuck::StuckStats;
 use crate::bike::{spawn_bikes, BicycleParams, apply_z_order};
 use crate::bike_config::{PlayerConfig, PlayerConfigChangedEvent};
 use crate::countdown::{race_setup, RaceCountdown};
-use crate::game_state::{despawn_all, GameConfig, GameState, RaceConfig, RaceState, restart_system};
+use crate::cup::{CupSession, Cups};
+use crate::elimination::EliminationPlugin;
+use crate::game_state::{
+    despawn_all, race_running, restart_system, GameConfig, GameState, RaceConfig, RaceState,
+};
+use crate::ghost::GhostPlugin;
 use crate::item_pickup::ItemPickupPlugin;
-use crate::map::spawn_map_system;
+use crate::lap_timer::{LapCompletedEvent, RaceClock, Sectors};
+use crate::map::{spawn_map_system, TrackWalls};
 use crate::ranking::{Progress, Rank, RankingPlugin};
-use crate::waypoint::Waypoint;
+use crate::records::RecordsPlugin;
+use crate::rush::RushPlugin;
+use crate::save::SavePlugin;
+use crate::slipstream::SlipstreamPlugin;
+use crate::team::TeamPlugin;
+use crate::tracks::TrackRegistry;
+use crate::waypoint::{TrackLength, Waypoint};
 use avian2d::prelude::{Gravity, PhysicsDebugPlugin, PhysicsSet};
 use avian2d::PhysicsPlugins;
 use bevy::asset::AssetMetaCheck;
@@ -64,7 +97,15 @@ fn main() {
             RankingPlugin,
             LassoPlugin,
             HookPlugin,
+            GhostPlugin,
+            EliminationPlugin,
+            ArenaPlugin,
+            RushPlugin,
+            TeamPlugin,
+            RecordsPlugin,
+            SavePlugin,
         ))
+        .add_plugins((BumpPlugin, SlipstreamPlugin))
         .insert_resource(EguiSettings {
             scale_factor: 1.5,
             default_open_url_target: Some("_blank".to_string()),
@@ -74,18 +115,37 @@ fn main() {
         .insert_state(RaceState::Countdown)
         .insert_resource(RaceCountdown::default())
         .insert_resource(GameConfig::default())
+        .init_resource::<TrackWalls>()
+        .init_resource::<RacingLine>()
+        .init_resource::<StuckStats>()
+        .init_resource::<TrackLength>()
+        .init_resource::<RaceClock>()
+        .init_resource::<Sectors>()
+        .init_resource::<CupSession>()
+        .init_resource::<Cups>()
+        .init_resource::<TrackRegistry>()
         .register_type::<BicycleParams>()
         .register_type::<Rank>()
         .register_type::<Progress>()
         .register_type::<Waypoint>()
+        .register_type::<AiSkill>()
         .add_systems(
             OnEnter(GameState::Race),
-            (despawn_all, race_setup, spawn_map_system, spawn_bikes).chain(),
+            (
+                despawn_all,
+                race_setup,
+                lap_timer::reset_race_clock,
+                spawn_map_system,
+                build_racing_line,
+                spawn_bikes,
+            )
+                .chain(),
         )
         .add_systems(
             OnEnter(GameState::Restart),
             restart_system,
         )
+        .add_systems(OnEnter(GameState::CupPodium), cup::finish_cup)
         .add_systems(
             OnEnter(GameState::MainMenu),
             (despawn_all, main_menu::setup_main_menu).chain(),
@@ -98,19 +158,32 @@ fn main() {
                         bike::control_player,
                         bike::drift_factor_system,
                         bike::bike_controller_system,
+                        ai::avoidance::sense_hazards_system.before(waypoint::follow_waypoint),
+                        ai::blocking::blocking_system.before(waypoint::follow_waypoint),
                         waypoint::follow_waypoint,
+                        ai::stuck::stuck_recovery_system.after(waypoint::follow_waypoint),
                         rocket::despawn_rocket_system,
+                        rocket::ignite_player_rocket_system,
+                        ai::items::ai_item_system,
+                        catch_up::catch_up_system,
+                        lap_timer::tick_race_clock,
+                        lap_timer::lap_timer_system
+                            .after(ranking::track_progress_system)
+                            .before(ranking::check_finish),
                     )
-                        .run_if(in_state(RaceState::Playing)),
+                        .run_if(race_running),
                     (finish_ui::finish_ui.run_if(in_state(RaceState::Finished)),),
                     finish_ui::lap_ui,
+                    ai::stuck::stuck_debug_ui,
                     bike::mirror_bike_system,
+                    name_tag::update_name_tags,
                     countdown::countdown_ui,
                     apply_z_order,
                 )
                     .run_if(in_state(GameState::Race)),
                 bike::apply_config_to_player.run_if(resource_changed::<PlayerConfig>),
                 main_menu::main_menu_ui.run_if(in_state(GameState::MainMenu)),
+                cup::podium_ui.run_if(in_state(GameState::CupPodium)),
                 click_sound_system,
             ),
         )
@@ -124,6 +197,7 @@ fn main() {
         .insert_resource(PlayerConfig::default())
         .insert_resource(RaceConfig::default())
         .add_event::<PlayerConfigChangedEvent>()
+        .add_event::<LapCompletedEvent>()
         .run();
 }
 
@@ -1,11 +1,25 @@
+use crate::ai::rivals::pick_rivals;
+use crate::ai::skill::AiDifficulty;
 use crate::bike::{Bicycle, BicycleParams, Player};
-use crate::bike_config::PlayerConfig;
+use crate::bike_config::{BicycleModTrait, PlayerConfig};
+use crate::catch_up::CatchUp;
 use crate::character_editor::character_editor;
-use crate::game_state::{DespawnMe, GameConfig, GameState, RaceConfig, RaceState, MAPS};
+use crate::cup::{cup_builder, Cup, CupSession, Cups};
+use crate::elimination::EliminationRule;
+use crate::game_state::{
+    layout_name, DespawnMe, GameConfig, GameState, RaceConfig, RaceState, RaceType,
+};
+use crate::lap_timer::format_time;
+use crate::records::{Records, ScoreRecord, TimeRecord};
+use crate::save::Settings;
+use crate::storage::SaveStorage;
+use crate::team::MAX_TEAMS;
+use crate::tracks::TrackRegistry;
 use bevy::prelude::*;
 use bevy::render::camera::ScalingMode;
 use bevy_egui::egui::{ComboBox, Id, ScrollArea, Visuals, Widget};
 use bevy_egui::{egui, EguiContexts};
+use enum_iterator::all;
 use std::f32::consts::PI;
 
 pub fn main_menu_ui(
@@ -15,6 +29,13 @@ pub fn main_menu_ui(
     mut next_state: ResMut<NextState<GameState>>,
     mut next_race_state: ResMut<NextState<RaceState>>,
     game_config: Res<GameConfig>,
+    records: Res<Records>,
+    mut settings: ResMut<Settings>,
+    mut cup: ResMut<CupSession>,
+    mut cups: ResMut<Cups>,
+    mut cup_draft: Local<Cup>,
+    tracks: Res<TrackRegistry>,
+    storage: Res<SaveStorage>,
 ) {
     let ctx = contexts.ctx_mut();
     ctx.set_visuals(Visuals::light());
@@ -29,46 +50,263 @@ pub fn main_menu_ui(
                 ui.set_width(width - ui.style().spacing.window_margin.left * 2.0);
                 ui.heading("Race Setup");
                 ui.horizontal(|ui| {
-                    ui.add_enabled_ui(game_config.level_selector_unlocked, |ui| {
-                        ui.selectable_value(&mut race_config.is_cup, false, "Single")
-                            .on_disabled_hover_text("Complete a cup to unlock the level selector");
-                    });
-                    ui.selectable_value(&mut race_config.is_cup, true, "Cup");
+                    for race_type in all::<RaceType>() {
+                        ui.selectable_value(&mut race_config.race_type, race_type, race_type.name());
+                    }
                 });
 
-                if !race_config.is_cup {
+                // Race types without cups have every map open
+                let always_open = !race_config.race_type.has_cups();
+                if !always_open {
+                    // The single race map selector has to be unlocked first
+                    if !game_config.level_selector_unlocked && !race_config.is_cup {
+                        race_config.is_cup = true;
+                    }
+                    ui.horizontal(|ui| {
+                        ui.add_enabled_ui(game_config.level_selector_unlocked, |ui| {
+                            ui.selectable_value(&mut race_config.is_cup, false, "Single")
+                                .on_disabled_hover_text("Complete a cup to unlock the level selector");
+                        });
+                        ui.selectable_value(&mut race_config.is_cup, true, "Cup");
+                    });
+                }
+
+                if !race_config.plays_cup() {
+                    // Battles need an arena and races a track
+                    let arena = race_config.race_type == RaceType::Arena;
+                    if tracks.get(&race_config.map).map_or(true, |track| track.arena != arena) {
+                        if let Some(track) = tracks.of_kind(arena).next() {
+                            race_config.map = track.name.clone();
+                        }
+                    }
+
                     ComboBox::new("Map", "Select Map")
                         .selected_text(&race_config.map)
                         .show_ui(ui, |ui| {
-                            for map in MAPS {
-                                if ui.selectable_label(race_config.map == *map, map).clicked() {
-                                    race_config.map = map.to_string();
+                            for track in tracks.of_kind(arena) {
+                                if ui.selectable_label(race_config.map == track.name, &track.name).clicked() {
+                                    race_config.map = track.name.clone();
                                 }
                             }
                         });
+
+                    ui.horizontal(|ui| {
+                        // Arenas have no direction to drive in
+                        if !arena {
+                            ui.checkbox(&mut race_config.reversed, "Reverse");
+                        }
+                        ui.checkbox(&mut race_config.mirrored, "Mirror");
+                    });
                 } else {
-                    race_config.map = MAPS[0].to_string();
+                    if cups.get(&race_config.cup).is_none() {
+                        race_config.cup = cups.builtin[0].name.clone();
+                    }
+
+                    ComboBox::new("Cup", "Select Cup")
+                        .selected_text(&race_config.cup)
+                        .show_ui(ui, |ui| {
+                            for cup in cups.all() {
+                                if ui.selectable_label(race_config.cup == cup.name, &cup.name).clicked() {
+                                    race_config.cup = cup.name.clone();
+                                }
+                            }
+                        });
+
+                    if let Some(selected) = cups.get(&race_config.cup) {
+                        for (idx, track) in selected.tracks.iter().enumerate() {
+                            ui.label(format!("{}. {} - {} laps", idx + 1, track.layout_name(), track.laps));
+                        }
+                        ui.label(format!("Difficulty: {}", selected.difficulty.name()));
+                    }
+
+                    let custom_idx = cups.custom.iter().position(|cup| cup.name == race_config.cup);
+                    if let Some(idx) = custom_idx {
+                        if ui.button("Delete Cup").clicked() {
+                            cups.custom.remove(idx);
+                            cups.save_custom(&storage);
+                        }
+                    }
+
+                    ui.add_enabled_ui(game_config.level_selector_unlocked, |ui| {
+                        egui::CollapsingHeader::new("Cup Builder").show(ui, |ui| {
+                            if cup_builder(ui, &mut cup_draft, &tracks) {
+                                let mut new_cup = std::mem::take(&mut *cup_draft);
+                                if cups.builtin.iter().any(|cup| cup.name == new_cup.name) {
+                                    new_cup.name.push_str(" (Custom)");
+                                }
+                                cups.custom.retain(|cup| cup.name != new_cup.name);
+                                race_config.cup = new_cup.name.clone();
+                                cups.custom.push(new_cup);
+                                cups.save_custom(&storage);
+                            }
+                        })
+                    })
+                    .response
+                    .on_disabled_hover_text("Complete a cup to unlock the cup builder");
                 }
 
-                ui.add_enabled_ui(game_config.level_selector_unlocked, |ui| {
-                    ui.label("AI Count:");
-                    egui::widgets::DragValue::new(&mut race_config.ai_count)
-                        .range(0..=20)
-                        .ui(ui)
-                        .on_disabled_hover_text("Complete a cup to unlock the level selector");
-
-                    ui.label("Laps:");
-                    egui::widgets::DragValue::new(&mut race_config.laps)
-                        .range(1..=10)
-                        .ui(ui)
-                        .on_disabled_hover_text("Complete a cup to unlock the level selector");
+                let rush = race_config.race_type == RaceType::CheckpointRush;
+                let has_bots = race_config.race_type.has_bots() && (!rush || race_config.rush_traffic);
+                ui.add_enabled_ui(game_config.level_selector_unlocked || always_open, |ui| {
+                    if rush {
+                        ui.checkbox(&mut race_config.rush_traffic, "Bot traffic");
+                    }
+
+                    if has_bots {
+                        ui.label("AI Count:");
+                        egui::widgets::DragValue::new(&mut race_config.ai_count)
+                            .range(0..=20)
+                            .ui(ui)
+                            .on_disabled_hover_text("Complete a cup to unlock the level selector");
+                    }
+
+                    // Cups bring their own lap counts and difficulty
+                    if !race_config.plays_cup() && race_config.race_type.has_laps() {
+                        ui.label("Laps:");
+                        egui::widgets::DragValue::new(&mut race_config.laps)
+                            .range(1..=10)
+                            .ui(ui)
+                            .on_disabled_hover_text("Complete a cup to unlock the level selector");
+                    }
                 });
 
-                if ui.button("Start Race").clicked() {
+                if has_bots {
+                    if !race_config.plays_cup() {
+                        ui.label("Difficulty:");
+                        ui.horizontal(|ui| {
+                            for difficulty in all::<AiDifficulty>() {
+                                ui.selectable_value(&mut race_config.difficulty, difficulty, difficulty.name());
+                            }
+                        });
+                    }
+
+                    if race_config.race_type == RaceType::Elimination {
+                        ui.label("Knockouts:");
+                        ui.horizontal(|ui| {
+                            for rule in all::<EliminationRule>() {
+                                ui.selectable_value(&mut race_config.elimination, rule, rule.name());
+                            }
+                            if race_config.elimination == EliminationRule::Timer {
+                                ui.add(
+                                    egui::DragValue::new(&mut race_config.elimination_interval)
+                                        .range(5..=120)
+                                        .suffix(" s"),
+                                );
+                            }
+                        });
+                    }
+
+                    if race_config.race_type.has_teams() {
+                        ui.label("Teams:");
+                        ui.horizontal(|ui| {
+                            ui.selectable_value(&mut race_config.teams, 0, "Off");
+                            for teams in 2..=MAX_TEAMS {
+                                ui.selectable_value(&mut race_config.teams, teams, teams.to_string());
+                            }
+                        });
+                    }
+
+                    ui.label("Catch-Up:");
+                    ui.horizontal(|ui| {
+                        for catch_up in all::<CatchUp>() {
+                            ui.selectable_value(&mut race_config.catch_up, catch_up, catch_up.name());
+                        }
+                    });
+                    ui.add_enabled(
+                        race_config.catch_up != CatchUp::Off,
+                        egui::Checkbox::new(&mut race_config.catch_up_items, "Better items when behind"),
+                    );
+                }
+
+                // Tracks of custom cups might come from a pack that was removed since
+                let selected_cup = cups.get(&race_config.cup).cloned().map(|mut cup| {
+                    cup.tracks.retain(|track| tracks.contains(&track.map));
+                    cup
+                });
+                let can_start = !race_config.plays_cup()
+                    || selected_cup.as_ref().is_some_and(|cup| !cup.tracks.is_empty());
+
+                if ui.add_enabled(can_start, egui::Button::new("Start Race")).clicked() {
+                    *cup = match selected_cup.filter(|_| race_config.plays_cup()) {
+                        Some(selected_cup) => {
+                            selected_cup.tracks[0].apply(&mut race_config);
+                            CupSession::start(
+                                selected_cup,
+                                pick_rivals(race_config.bot_count(), &player_config.0.skin),
+                            )
+                        }
+                        None => CupSession::default(),
+                    };
                     next_state.set(GameState::Race);
                     next_race_state.set(RaceState::Countdown);
                 }
             });
+
+            ui.group(|ui| {
+                ui.set_width(width - ui.style().spacing.window_margin.left * 2.0);
+                ui.heading("Records");
+
+                let time_record = |record: &Option<TimeRecord>| {
+                    record.as_ref().map_or("-".to_string(), |record| {
+                        format!("{} ({})", format_time(record.time), record.character.skin.name())
+                    })
+                };
+                let score_record = |record: &ScoreRecord| {
+                    format!("{:.0} m ({})", record.distance, record.character.skin.name())
+                };
+
+                egui::Grid::new("Records").striped(true).show(ui, |ui| {
+                    ui.label("Track");
+                    ui.label("Laps");
+                    ui.label("Type");
+                    ui.label("Race");
+                    ui.label("Lap");
+                    ui.label("Cup");
+                    ui.end_row();
+
+                    for track in &records.tracks {
+                        ui.label(layout_name(&track.map, track.reversed, track.mirrored));
+                        if track.race_type.has_laps() {
+                            ui.label(track.laps.to_string());
+                        } else {
+                            ui.label("-");
+                        }
+                        ui.label(track.race_type.name());
+                        match &track.best_rush {
+                            Some(record) => ui.label(score_record(record)),
+                            None => ui.label(time_record(&track.best_race)),
+                        };
+                        ui.label(time_record(&track.best_lap));
+                        ui.label(
+                            track
+                                .best_cup
+                                .as_ref()
+                                .map_or("-".to_string(), |record| format!("{}.", record.position)),
+                        );
+                        ui.end_row();
+                    }
+                });
+
+                if records.tracks.is_empty() {
+                    ui.label("Finish a race to set your first record!");
+                }
+            });
+
+            ui.group(|ui| {
+                ui.set_width(width - ui.style().spacing.window_margin.left * 2.0);
+                ui.heading("Settings");
+
+                let mut volume = settings.volume;
+                ui.add(egui::Slider::new(&mut volume, 0.0..=2.0).text("Volume"));
+                let mut ui_scale = settings.ui_scale;
+                ui.add(egui::Slider::new(&mut ui_scale, 1.0..=2.5).text("UI Scale"));
+
+                // Only touch the settings when they really change, the UI scale is applied on change
+                if volume != settings.volume || ui_scale != settings.ui_scale {
+                    settings.volume = volume;
+                    settings.ui_scale = ui_scale;
+                }
+            });
         });
     });
 }
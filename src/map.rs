@@ -1,7 +1,10 @@
-use crate::game_state::{DespawnMe, MAP_DATA, MAPS, RaceConfig};
+use crate::arena::{ArenaSpawns, ARENA_ITEM_RESPAWN};
+use crate::game_state::{DespawnMe, RaceConfig, RaceType};
 use crate::item_pickup::ItemPickup;
+use crate::lap_timer::Sectors;
 use crate::slow::Slow;
-use crate::waypoint::Waypoint;
+use crate::tracks::TrackRegistry;
+use crate::waypoint::{TrackLength, Waypoint};
 use avian2d::math::Vector;
 use avian2d::prelude::{Collider, Position, RigidBody, Rotation, VhacdParameters};
 use bevy::asset::io::embedded::EmbeddedAssetRegistry;
@@ -17,12 +20,59 @@ use svg::node::element::tag::Type;
 use svg::node::Value;
 use svg::parser::Event;
 
+/// The outlines of all collider polygons, used to keep the racing line on the track
+#[derive(Resource, Debug, Default)]
+pub struct TrackWalls {
+    pub segments: Vec<(Vec2, Vec2)>,
+}
+
+impl TrackWalls {
+    /// Distance from `origin` along `direction` until the first wall is hit
+    pub fn free_distance(&self, origin: Vec2, direction: Vec2) -> f32 {
+        self.segments
+            .iter()
+            .filter_map(|(a, b)| {
+                let edge = *b - *a;
+                let denominator = direction.perp_dot(edge);
+                if denominator.abs() < f32::EPSILON {
+                    return None;
+                }
+                let to_a = *a - origin;
+                let t = to_a.perp_dot(edge) / denominator;
+                let u = to_a.perp_dot(direction) / denominator;
+                (t >= 0.0 && (0.0..=1.0).contains(&u)).then_some(t)
+            })
+            .fold(f32::INFINITY, f32::min)
+    }
+}
+
+/// Flips `x` around the center of a map that is `mirror_width` wide, if the map is mirrored
+fn mirror(x: f32, mirror_width: Option<f32>) -> f32 {
+    mirror_width.map_or(x, |width| width - x)
+}
+
+/// The `mirror_width` that flips a map around the center of its viewBox (`min-x min-y width
+/// height`). Unlike the `width` attribute this is in the same units as the geometry.
+fn view_box_width(view_box: &str) -> Option<f32> {
+    let parts: Vec<f32> = view_box
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+
+    match parts[..] {
+        [min_x, _, width, _] if width > 0.0 => Some(min_x * 2.0 + width),
+        _ => None,
+    }
+}
+
 pub fn spawn_map_system(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     assets: ResMut<EmbeddedAssetRegistry>,
     race_config: Res<RaceConfig>,
+    tracks: Res<TrackRegistry>,
 ) {
     // let texture_handle = asset_server.load("map.webp");
     // let transform = Transform::from_scale(Vec3::splat(1.0 / 20.0));
@@ -32,28 +82,33 @@ pub fn spawn_map_system(
     //     ..Default::default()
     // });
 
-    let map_idx = MAPS
-        .iter()
-        .position(|map| map == &race_config.map)
-        .unwrap_or(0);
-
-    let map = MAP_DATA[map_idx];
+    let track = tracks
+        .get(&race_config.map)
+        .unwrap_or_else(|| tracks.default_track());
 
-    let svg = svg::read(map).unwrap();
+    let svg = svg::read(&track.svg).unwrap();
 
-    //let mut view_box = None;
+    let mut walls = TrackWalls::default();
+    let mut spawns = ArenaSpawns::default();
+    let mut pickup_count = 0;
+    // The map width when the map is mirrored, every x coordinate is flipped around its center
+    let mut mirror_width = None;
 
     for item in svg {
         match item {
             Event::Tag(tag::SVG, _, attrs) => {
-                // view_box = attrs.get("viewBox").map(Deref::deref).map(|s| {
-                //     let mut parts = s.split(' ').map(|s| s.parse().unwrap());
-                //     let x = parts.next().unwrap();
-                //     let y = parts.next().unwrap();
-                //     let width = parts.next().unwrap();
-                //     let height = parts.next().unwrap();
-                //     (x, y, width, height)
-                // });
+                if race_config.mirrored {
+                    mirror_width = attrs
+                        .get("viewBox")
+                        .map(Deref::deref)
+                        .and_then(view_box_width);
+                    if mirror_width.is_none() {
+                        error!(
+                            "{} has no usable viewBox and can't be mirrored, racing it unmirrored",
+                            track.name
+                        );
+                    }
+                }
             }
             Event::Tag(tag::Image, _, attrs) => {
                 if let Some(href) = attrs.get("xlink:href") {
@@ -66,13 +121,14 @@ pub fn spawn_map_system(
                     //     vec,
                     // );
 
-                    let map_image = asset_server.load(format!("maps/{}", href));
+                    let map_image = asset_server.load(format!("{}/{}", track.asset_dir, href));
 
                     let width = attrs.get("width").unwrap().parse().unwrap();
                     let height = attrs.get("height").unwrap().parse::<f32>().unwrap();
 
+                    // The image is flipped in place, so its center has to be mirrored like the rest
                     let center = Transform::from_translation(Vec3::new(
-                        width / 2.0,
+                        mirror(width / 2.0, mirror_width),
                         // Invert height because the SVG coordinate system is flipped in comparison to Bevy's
                         -height / 2.0,
                         0.0,
@@ -87,12 +143,13 @@ pub fn spawn_map_system(
                             transform: map_transform,
                             sprite: Sprite {
                                 custom_size: Some(Vec2::new(width, height)),
+                                flip_x: mirror_width.is_some(),
                                 ..Default::default()
                             },
                             ..Default::default()
                         },
                         AudioBundle {
-                            source: asset_server.load(format!("music/{}.mp3", race_config.map)),
+                            source: asset_server.load(track.music.clone()),
                             settings: PlaybackSettings::LOOP,
                         },
                         DespawnMe,
@@ -116,7 +173,7 @@ pub fn spawn_map_system(
             }
             Event::Tag(tag::Polygon, _, attrs) => {
                 let points = attrs.get("points").unwrap();
-                let points: Vec<(f32, f32)> = points
+                let mut points: Vec<(f32, f32)> = points
                     .split(' ')
                     .tuples()
                     .map(|(x, y)| {
@@ -128,16 +185,63 @@ pub fn spawn_map_system(
                     })
                     .collect();
 
+                let is_track = attrs.get("id").map(Deref::deref) == Some("track");
+                // A reversed track keeps its start line but visits the waypoints the other way around
+                if is_track && race_config.reversed {
+                    points[1..].reverse();
+                }
+                let outline: Vec<(f32, f32)> = points
+                    .iter()
+                    .map(|(x, y)| (mirror(*x, mirror_width), *y))
+                    .collect();
+
                 let mut first = None;
                 let mut prev = None;
 
-                if attrs.get("id").map(Deref::deref) == Some("track") {
-                    for (idx, (x, y)) in points.iter().enumerate().rev() {
+                let lap_distances: Vec<f32> = points
+                    .iter()
+                    .scan((0.0, points[0]), |(distance, last), point| {
+                        *distance += Vec2::new(point.0 - last.0, point.1 - last.1).length();
+                        *last = *point;
+                        Some(*distance)
+                    })
+                    .collect();
+
+                if is_track {
+                    let (last_x, last_y) = points[points.len() - 1];
+                    let lap_length = lap_distances[points.len() - 1]
+                        + Vec2::new(points[0].0 - last_x, points[0].1 - last_y).length();
+                    commands.insert_resource(TrackLength(lap_length));
+
+                    // Sector ends can be set as waypoint indices, like `data-sectors="6 14"`
+                    let sectors = attrs
+                        .get("data-sectors")
+                        .map(|sectors| {
+                            Sectors(
+                                sectors
+                                    .split_whitespace()
+                                    .filter_map(|idx| idx.parse::<usize>().ok())
+                                    .map(|idx| {
+                                        if race_config.reversed {
+                                            (points.len() - idx) % points.len()
+                                        } else {
+                                            idx
+                                        }
+                                    })
+                                    .sorted()
+                                    .collect(),
+                            )
+                        })
+                        .unwrap_or_else(|| Sectors::thirds(points.len()));
+                    commands.insert_resource(sectors);
+
+                    for (idx, (x, y)) in outline.iter().enumerate().rev() {
                         let mut entity = commands.spawn((
                             Name::new(format!("Waypoint {}", idx)),
                             Waypoint {
                                 next: prev,
                                 index: idx,
+                                lap_distance: lap_distances[idx],
                             },
                             TransformBundle {
                                 local: Transform::from_translation(Vec3::new(*x, *y, 0.0)),
@@ -159,6 +263,7 @@ pub fn spawn_map_system(
                     commands.entity(first).insert(Waypoint {
                         next: prev,
                         index: points.len(),
+                        lap_distance: lap_distances[points.len() - 1],
                     });
                 }
 
@@ -167,6 +272,15 @@ pub fn spawn_map_system(
                     .map(|s| s.split(' ').collect::<Vec<_>>())
                     .unwrap_or_default();
 
+                if classes.contains(&"collider") {
+                    walls.segments.extend(
+                        outline
+                            .iter()
+                            .circular_tuple_windows()
+                            .map(|((x1, y1), (x2, y2))| (Vec2::new(*x1, *y1), Vec2::new(*x2, *y2))),
+                    );
+                }
+
                 let mut buffers = VertexBuffers::new();
 
                 let mut builder = SimpleBuffersBuilder::new(&mut buffers, Positions);
@@ -190,21 +304,17 @@ pub fn spawn_map_system(
                 builder.build().unwrap();
 
                 buffers.indices.iter().tuples().for_each(|(i, j, k)| {
+                    let vertex = |idx: &u16| {
+                        let vertex = buffers.vertices[*idx as usize];
+                        Vector::new(mirror(vertex.x, mirror_width), vertex.y)
+                    };
                     // Triangle order is important!: https://github.com/Jondolf/avian/issues/368
-                    let collider = Collider::triangle(
-                        Vector::new(
-                            buffers.vertices[*i as usize].x,
-                            buffers.vertices[*i as usize].y,
-                        ),
-                        Vector::new(
-                            buffers.vertices[*k as usize].x,
-                            buffers.vertices[*k as usize].y,
-                        ),
-                        Vector::new(
-                            buffers.vertices[*j as usize].x,
-                            buffers.vertices[*j as usize].y,
-                        ),
-                    );
+                    // Mirroring flips the winding, so the order has to be flipped back
+                    let collider = if mirror_width.is_some() {
+                        Collider::triangle(vertex(i), vertex(j), vertex(k))
+                    } else {
+                        Collider::triangle(vertex(i), vertex(k), vertex(j))
+                    };
 
                     if classes.contains(&"collider") {
                         commands.spawn((RigidBody::Static, collider, DespawnMe));
@@ -214,12 +324,31 @@ pub fn spawn_map_system(
                 });
             }
             Event::Tag(tag::Circle, _, attrs) => {
-                let cx = attrs.get("cx").unwrap().parse().unwrap();
+                let cx = mirror(attrs.get("cx").unwrap().parse().unwrap(), mirror_width);
                 let cy = -attrs.get("cy").unwrap().parse::<f32>().unwrap();
 
                 let collider = Collider::circle(0.5);
 
-                if attrs.get("class").map(Deref::deref) == Some("pickup") {
+                if attrs.get("class").map(Deref::deref) == Some("spawn") {
+                    spawns.0.push(Vec2::new(cx, cy));
+                }
+
+                let is_pickup = attrs.get("class").map(Deref::deref) == Some("pickup");
+                if is_pickup {
+                    pickup_count += 1;
+                }
+                // Rush runs only get every other pickup to keep them hard
+                let skip_pickup =
+                    race_config.race_type == RaceType::CheckpointRush && pickup_count % 2 == 0;
+
+                if is_pickup && race_config.race_type.has_items() && !skip_pickup {
+                    // Battles are all about the items, so they come back quicker
+                    let pickup = if race_config.race_type == RaceType::Arena {
+                        ItemPickup::with_respawn(ARENA_ITEM_RESPAWN)
+                    } else {
+                        ItemPickup::default()
+                    };
+
                     let aspect = 782.0 / 868.0;
 
                     commands.spawn((
@@ -232,7 +361,7 @@ pub fn spawn_map_system(
                             ..Default::default()
                         },
                         collider,
-                        ItemPickup::default(),
+                        pickup,
                         DespawnMe,
                     ));
                 }
@@ -240,4 +369,7 @@ pub fn spawn_map_system(
             _ => {}
         }
     }
+
+    commands.insert_resource(walls);
+    commands.insert_resource(spawns);
 }
@@ -0,0 +1,46 @@
+use crate::game_state::DespawnMe;
+use bevy::prelude::*;
+
+/// Height above the bike the name is shown at
+const NAME_TAG_OFFSET: f32 = 1.6;
+
+/// Shows the name of a racer above its bike. This is not a child of the bike so it doesn't
+/// rotate and mirror with it.
+#[derive(Component, Debug)]
+pub struct NameTag {
+    pub bike: Entity,
+}
+
+pub fn spawn_name_tag(commands: &mut Commands, bike: Entity, name: &str, color: Color) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                name,
+                TextStyle {
+                    font_size: 40.0,
+                    color,
+                    ..Default::default()
+                },
+            ),
+            transform: Transform::from_scale(Vec3::splat(0.015)),
+            ..Default::default()
+        },
+        NameTag { bike },
+        DespawnMe,
+    ));
+}
+
+pub fn update_name_tags(
+    mut commands: Commands,
+    mut tag_query: Query<(Entity, &NameTag, &mut Transform)>,
+    bike_query: Query<&GlobalTransform>,
+) {
+    for (entity, tag, mut transform) in tag_query.iter_mut() {
+        if let Ok(bike_transform) = bike_query.get(tag.bike) {
+            let position = bike_transform.translation();
+            transform.translation = Vec3::new(position.x, position.y + NAME_TAG_OFFSET, 1000.0);
+        } else {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -1,8 +1,11 @@
 use crate::bike::{Bicycle, Player};
-use crate::game_state::{GameState, RaceConfig, RaceState};
+use crate::elimination::Eliminated;
+use crate::game_state::{on_track, race_running, GameState, RaceConfig, RaceState};
+use crate::lap_timer::{LapTimes, RaceClock};
 use crate::waypoint::Waypoint;
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts};
+use std::cmp::Ordering;
 
 pub struct RankingPlugin;
 
@@ -10,7 +13,8 @@ impl Plugin for RankingPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            (track_progress_system, rank_bicycles_system, check_finish).run_if(in_state(GameState::Race).and_then(in_state(RaceState::Playing))),
+            (track_progress_system, rank_bicycles_system, check_finish)
+                .run_if(in_state(GameState::Race).and_then(race_running).and_then(on_track)),
         );
     }
 }
@@ -26,23 +30,66 @@ pub struct Progress {
     pub next_checkpoint: Entity,
 }
 
+/// Added once a racer completed all laps of the race
+#[derive(Debug, Component, Clone, Copy)]
+pub struct FinishTime {
+    /// Race clock time at which the racer crossed the finish line
+    pub time: f32,
+    pub best_lap: Option<f32>,
+}
+
+impl Progress {
+    /// Distance covered along the track since the start, counting all laps
+    pub fn race_distance(&self, next_checkpoint: &Waypoint, lap_length: f32) -> f32 {
+        // The start line is the waypoint with index 0, crossing it starts the next round
+        let distance_into_lap = if next_checkpoint.index == 0 {
+            lap_length
+        } else {
+            next_checkpoint.lap_distance
+        };
+        (self.round as f32 - 1.0) * lap_length + distance_into_lap
+            - self.distance_to_next_checkpoint
+    }
+
+    /// Estimates when the racer will finish, assuming it keeps its average speed so far
+    pub fn projected_finish_time(
+        &self,
+        next_checkpoint: &Waypoint,
+        lap_length: f32,
+        laps: usize,
+        clock: &RaceClock,
+    ) -> Option<f32> {
+        let covered = self.race_distance(next_checkpoint, lap_length);
+        (covered > 0.0).then(|| clock.0 * laps as f32 * lap_length / covered)
+    }
+}
+
 pub const NEXT_CHECKPOINT_DISTANCE: f32 = 5.0;
 
-pub fn rank_bicycles_system(mut query: Query<(&Bicycle, &Progress, &mut Rank)>) {
+pub fn rank_bicycles_system(
+    mut query: Query<(&Bicycle, &Progress, Option<&FinishTime>, &mut Rank), Without<Eliminated>>,
+) {
     let mut all = query.iter_mut().collect::<Vec<_>>();
 
-    all.sort_by(|(_, a, _), (_, b, _)| {
-        a.round
-            .cmp(&b.round)
-            .then_with(|| a.checkpoint_idx.cmp(&b.checkpoint_idx))
-            .then_with(|| {
-                b.distance_to_next_checkpoint
-                    .partial_cmp(&a.distance_to_next_checkpoint)
-                    .unwrap()
-            })
+    all.sort_by(|(_, a, a_finish, _), (_, b, b_finish, _)| {
+        // Finished racers keep the order they crossed the line in, even while they keep driving
+        match (a_finish, b_finish) {
+            (Some(a), Some(b)) => b.time.total_cmp(&a.time),
+            (Some(_), None) => Ordering::Greater,
+            (None, Some(_)) => Ordering::Less,
+            (None, None) => a
+                .round
+                .cmp(&b.round)
+                .then_with(|| a.checkpoint_idx.cmp(&b.checkpoint_idx))
+                .then_with(|| {
+                    b.distance_to_next_checkpoint
+                        .partial_cmp(&a.distance_to_next_checkpoint)
+                        .unwrap()
+                }),
+        }
     });
 
-    for (i, (_, _, mut rank)) in all.into_iter().rev().enumerate() {
+    for (i, (_, _, _, mut rank)) in all.into_iter().rev().enumerate() {
         rank.0 = i + 1;
     }
 }
@@ -85,11 +132,26 @@ pub fn check_finish(
     mut commands: Commands,
     assets: Res<AssetServer>,
     race_config: Res<RaceConfig>,
+    clock: Res<RaceClock>,
     mut next_race_state: ResMut<NextState<RaceState>>,
-    player_progress_query: Query<(&Player, &Progress)>,
+    racer_query: Query<(Entity, &Progress, &LapTimes, Has<Player>), Without<FinishTime>>,
 ) {
-    if let Some((_, progress)) = player_progress_query.iter().next() {
-        if progress.round > race_config.laps {
+    // Elimination races end when one racer is left, see `elimination_system`
+    if !race_config.race_type.has_laps() {
+        return;
+    }
+
+    for (entity, progress, times, player) in racer_query.iter() {
+        if progress.round <= race_config.laps {
+            continue;
+        }
+
+        commands.entity(entity).insert(FinishTime {
+            time: clock.0,
+            best_lap: times.best_lap(),
+        });
+
+        if player {
             next_race_state.set(RaceState::Finished);
 
             commands.spawn((
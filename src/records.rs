@@ -0,0 +1,216 @@
+use crate::bike::Player;
+use crate::bike_config::{CharacterConfig, PlayerConfig};
+use crate::game_state::{GameState, RaceConfig, RaceState, RaceType};
+use crate::ranking::{FinishTime, Rank};
+use crate::rush::Rush;
+use crate::storage::SaveStorage;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const RECORDS_KEY: &str = "records";
+
+pub struct RecordsPlugin;
+
+impl Plugin for RecordsPlugin {
+    fn build(&self, app: &mut App) {
+        let storage = SaveStorage::default();
+        let records = Records::load(&storage);
+
+        app.insert_resource(storage)
+            .insert_resource(records)
+            .init_resource::<NewRecords>()
+            .add_systems(OnEnter(GameState::Race), reset_new_records)
+            .add_systems(OnEnter(RaceState::Finished), update_records_system);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeRecord {
+    pub time: f32,
+    pub character: CharacterConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CupRecord {
+    pub position: usize,
+    pub character: CharacterConfig,
+}
+
+/// The furthest checkpoint rush run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreRecord {
+    /// Distance along the track, counting all laps
+    pub distance: f32,
+    pub laps: usize,
+    pub character: CharacterConfig,
+}
+
+/// The records of one track for a lap count and race type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackRecords {
+    pub map: String,
+    #[serde(default)]
+    pub reversed: bool,
+    #[serde(default)]
+    pub mirrored: bool,
+    pub laps: usize,
+    pub race_type: RaceType,
+    pub best_race: Option<TimeRecord>,
+    pub best_lap: Option<TimeRecord>,
+    /// Best position reached on this track during a cup
+    pub best_cup: Option<CupRecord>,
+    #[serde(default)]
+    pub best_rush: Option<ScoreRecord>,
+}
+
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+pub struct Records {
+    pub tracks: Vec<TrackRecords>,
+}
+
+impl Records {
+    pub fn load(storage: &SaveStorage) -> Self {
+        storage.load(RECORDS_KEY).unwrap_or_default()
+    }
+
+    pub fn save(&self, storage: &SaveStorage) {
+        storage.save(RECORDS_KEY, self);
+    }
+
+    pub fn get(&self, config: &RaceConfig) -> Option<&TrackRecords> {
+        self.tracks.iter().find(|records| records.matches(config))
+    }
+
+    fn entry(&mut self, config: &RaceConfig) -> &mut TrackRecords {
+        let idx = self.tracks.iter().position(|records| records.matches(config));
+
+        match idx {
+            Some(idx) => &mut self.tracks[idx],
+            None => {
+                self.tracks.push(TrackRecords {
+                    map: config.map.clone(),
+                    reversed: config.reversed,
+                    mirrored: config.mirrored,
+                    laps: record_laps(config),
+                    race_type: config.race_type,
+                    best_race: None,
+                    best_lap: None,
+                    best_cup: None,
+                    best_rush: None,
+                });
+                self.tracks.last_mut().unwrap()
+            }
+        }
+    }
+}
+
+/// The lap count records are kept for, races without laps keep one set of records
+fn record_laps(config: &RaceConfig) -> usize {
+    if config.race_type.has_laps() {
+        config.laps
+    } else {
+        0
+    }
+}
+
+impl TrackRecords {
+    fn matches(&self, config: &RaceConfig) -> bool {
+        self.map == config.map
+            && self.reversed == config.reversed
+            && self.mirrored == config.mirrored
+            && self.laps == record_laps(config)
+            && self.race_type == config.race_type
+    }
+}
+
+/// Which records the player broke in the race that just finished
+#[derive(Resource, Debug, Default)]
+pub struct NewRecords {
+    pub race: bool,
+    pub lap: bool,
+    pub cup: bool,
+    pub rush: bool,
+}
+
+impl NewRecords {
+    pub fn any(&self) -> bool {
+        self.race || self.lap || self.cup || self.rush
+    }
+}
+
+fn reset_new_records(mut new_records: ResMut<NewRecords>) {
+    *new_records = NewRecords::default();
+}
+
+pub fn update_records_system(
+    player_query: Query<(&FinishTime, &Rank), With<Player>>,
+    race_config: Res<RaceConfig>,
+    player_config: Res<PlayerConfig>,
+    storage: Res<SaveStorage>,
+    mut records: ResMut<Records>,
+    mut new_records: ResMut<NewRecords>,
+    rush: Res<Rush>,
+) {
+    let Ok((finish, rank)) = player_query.get_single() else {
+        return;
+    };
+    let character = &player_config.0;
+    let track = records.entry(&race_config);
+
+    // A rush run always ends with the clock at zero, only the distance counts
+    if race_config.race_type == RaceType::CheckpointRush {
+        if track
+            .best_rush
+            .as_ref()
+            .map_or(true, |record| rush.distance > record.distance)
+        {
+            track.best_rush = Some(ScoreRecord {
+                distance: rush.distance,
+                laps: rush.laps,
+                character: character.clone(),
+            });
+            new_records.rush = true;
+        }
+    } else if track
+        .best_race
+        .as_ref()
+        .map_or(true, |record| finish.time < record.time)
+    {
+        track.best_race = Some(TimeRecord {
+            time: finish.time,
+            character: character.clone(),
+        });
+        new_records.race = true;
+    }
+
+    if let Some(best_lap) = finish.best_lap {
+        if track
+            .best_lap
+            .as_ref()
+            .map_or(true, |record| best_lap < record.time)
+        {
+            track.best_lap = Some(TimeRecord {
+                time: best_lap,
+                character: character.clone(),
+            });
+            new_records.lap = true;
+        }
+    }
+
+    if race_config.plays_cup()
+        && track
+            .best_cup
+            .as_ref()
+            .map_or(true, |record| rank.0 < record.position)
+    {
+        track.best_cup = Some(CupRecord {
+            position: rank.0,
+            character: character.clone(),
+        });
+        new_records.cup = true;
+    }
+
+    if new_records.any() {
+        records.save(&storage);
+    }
+}
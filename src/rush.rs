@@ -0,0 +1,112 @@
+use crate::bike::Player;
+use crate::game_state::{GameState, RaceConfig, RaceState, RaceType};
+use crate::lap_timer::{LapTimes, RaceClock};
+use crate::ranking::{track_progress_system, FinishTime, Progress};
+use crate::waypoint::{TrackLength, Waypoint};
+use bevy::prelude::*;
+
+/// Seconds on the clock at the start of a run
+const START_TIME: f32 = 15.0;
+/// Seconds earned per unit of track driven, at 5 units per second at most this needs a
+/// good pace to keep up
+const SECONDS_PER_UNIT: f32 = 0.28;
+/// Every lap the time earned per checkpoint shrinks by this factor
+const HARDER_PER_LAP: f32 = 0.93;
+/// The time earned never drops below this fraction
+const MIN_BONUS_FACTOR: f32 = 0.5;
+
+pub struct RushPlugin;
+
+impl Plugin for RushPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rush>()
+            .add_systems(OnEnter(GameState::Race), reset_rush)
+            .add_systems(
+                Update,
+                rush_system
+                    .after(track_progress_system)
+                    .run_if(in_state(GameState::Race).and_then(in_state(RaceState::Playing))),
+            );
+    }
+}
+
+/// The state of the player's checkpoint rush run
+#[derive(Resource, Debug)]
+pub struct Rush {
+    pub time_left: f32,
+    /// Seconds earned at the last checkpoint
+    pub last_bonus: Option<f32>,
+    /// Distance along the track covered so far, the score
+    pub distance: f32,
+    pub laps: usize,
+    checkpoint_idx: usize,
+    /// Distance at the last checkpoint, the time earned depends on the distance since then
+    checkpoint_distance: f32,
+}
+
+impl Default for Rush {
+    fn default() -> Self {
+        Self {
+            time_left: START_TIME,
+            last_bonus: None,
+            distance: 0.0,
+            laps: 0,
+            checkpoint_idx: 0,
+            checkpoint_distance: 0.0,
+        }
+    }
+}
+
+fn reset_rush(mut rush: ResMut<Rush>) {
+    *rush = Rush::default();
+}
+
+/// Counts down the player's time, adds time for every checkpoint passed and ends the run when
+/// the time runs out
+pub fn rush_system(
+    mut commands: Commands,
+    player_query: Query<(Entity, &Progress, &LapTimes), (With<Player>, Without<FinishTime>)>,
+    waypoint_query: Query<&Waypoint>,
+    mut rush: ResMut<Rush>,
+    mut next_race_state: ResMut<NextState<RaceState>>,
+    race_config: Res<RaceConfig>,
+    track_length: Res<TrackLength>,
+    clock: Res<RaceClock>,
+    time: Res<Time>,
+) {
+    if race_config.race_type != RaceType::CheckpointRush {
+        return;
+    }
+    let Ok((player, progress, times)) = player_query.get_single() else {
+        return;
+    };
+
+    if let Ok(waypoint) = waypoint_query.get(progress.next_checkpoint) {
+        rush.distance = rush
+            .distance
+            .max(progress.race_distance(waypoint, track_length.0));
+    }
+    rush.laps = times.laps.len();
+
+    // The first checkpoint is the start line, which the run starts behind
+    if progress.checkpoint_idx != rush.checkpoint_idx && progress.round > 0 {
+        let factor = HARDER_PER_LAP.powi(rush.laps as i32).max(MIN_BONUS_FACTOR);
+        let bonus = (rush.distance - rush.checkpoint_distance) * SECONDS_PER_UNIT * factor;
+
+        rush.time_left += bonus;
+        rush.last_bonus = Some(bonus);
+        rush.checkpoint_distance = rush.distance;
+    }
+    rush.checkpoint_idx = progress.checkpoint_idx;
+
+    rush.time_left -= time.delta_seconds();
+    if rush.time_left <= 0.0 {
+        rush.time_left = 0.0;
+
+        commands.entity(player).insert(FinishTime {
+            time: clock.0,
+            best_lap: times.best_lap(),
+        });
+        next_race_state.set(RaceState::Finished);
+    }
+}
@@ -0,0 +1,190 @@
+use crate::bike_config::{CharacterConfig, PlayerConfig};
+use crate::game_state::{GameConfig, RaceConfig};
+use crate::storage::SaveStorage;
+use crate::tracks::TrackRegistry;
+use bevy::audio::{GlobalVolume, Volume};
+use bevy::prelude::*;
+use bevy_egui::EguiSettings;
+use serde::{Deserialize, Serialize};
+
+const SAVE_KEY: &str = "save";
+/// Where a save that couldn't be read is moved to, so it isn't overwritten and lost
+const BROKEN_SAVE_KEY: &str = "save_broken";
+/// Bump this when a change to [`SaveGame`] can't be handled by `#[serde(default)]` alone,
+/// and add a migration from the old format to [`migrate`]
+const SAVE_VERSION: u32 = 1;
+/// Seconds without further changes before the save is written, so dragging a slider in the menu
+/// doesn't write the file every frame
+const SAVE_DELAY: f32 = 1.0;
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Settings>()
+            .init_resource::<PendingSave>()
+            .add_systems(Startup, load_save_game)
+            .add_systems(
+                Update,
+                (
+                    apply_settings_system.run_if(resource_changed::<Settings>),
+                    schedule_save_system.run_if(
+                        resource_changed::<GameConfig>
+                            .or_else(resource_changed::<PlayerConfig>)
+                            .or_else(resource_changed::<RaceConfig>)
+                            .or_else(resource_changed::<Settings>),
+                    ),
+                ),
+            )
+            // Last, so a pending save is still written in the frame the game is closed
+            .add_systems(Last, save_game_system);
+    }
+}
+
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub volume: f32,
+    pub ui_scale: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            ui_scale: 1.5,
+        }
+    }
+}
+
+/// Everything that is kept between sessions, apart from the records
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+struct SaveGame {
+    version: u32,
+    level_selector_unlocked: bool,
+    player: CharacterConfig,
+    race: RaceConfig,
+    settings: Settings,
+}
+
+impl Default for SaveGame {
+    fn default() -> Self {
+        Self {
+            version: SAVE_VERSION,
+            level_selector_unlocked: false,
+            player: CharacterConfig::default(),
+            race: RaceConfig::default(),
+            settings: Settings::default(),
+        }
+    }
+}
+
+/// Just the version, which every save format has
+#[derive(Deserialize)]
+struct SaveVersion {
+    version: u32,
+}
+
+/// Reads a save of any known version and converts it to the current format
+fn migrate(text: &str) -> Result<SaveGame, String> {
+    let SaveVersion { version } = ron::from_str(text).map_err(|err| err.to_string())?;
+
+    match version {
+        SAVE_VERSION => ron::from_str(text).map_err(|err| err.to_string()),
+        version => Err(format!(
+            "Save version {} is not supported, expected {}",
+            version, SAVE_VERSION
+        )),
+    }
+}
+
+fn load_save_game(
+    storage: Res<SaveStorage>,
+    mut game_config: ResMut<GameConfig>,
+    mut player_config: ResMut<PlayerConfig>,
+    mut race_config: ResMut<RaceConfig>,
+    mut settings: ResMut<Settings>,
+    tracks: Res<TrackRegistry>,
+) {
+    let Some(text) = storage.0.load(SAVE_KEY) else {
+        return;
+    };
+
+    match migrate(&text) {
+        Ok(save) => {
+            game_config.level_selector_unlocked = save.level_selector_unlocked;
+            player_config.0 = save.player;
+            *race_config = save.race;
+            if !tracks.contains(&race_config.map) {
+                race_config.map = tracks.default_track().name.clone();
+            }
+            *settings = save.settings;
+        }
+        Err(err) => {
+            warn!("Failed to load save, starting fresh: {}", err);
+            storage.0.save(BROKEN_SAVE_KEY, &text);
+        }
+    }
+}
+
+/// The save text waiting to be written, and the countdown until it is
+#[derive(Resource, Debug, Default)]
+struct PendingSave {
+    text: String,
+    timer: Option<Timer>,
+}
+
+/// Restarts the countdown whenever the save would come out different. The menu touches the
+/// configs every frame, so them being marked as changed alone doesn't mean anything changed.
+fn schedule_save_system(
+    game_config: Res<GameConfig>,
+    player_config: Res<PlayerConfig>,
+    race_config: Res<RaceConfig>,
+    settings: Res<Settings>,
+    mut pending: ResMut<PendingSave>,
+) {
+    let save = SaveGame {
+        version: SAVE_VERSION,
+        level_selector_unlocked: game_config.level_selector_unlocked,
+        player: player_config.0.clone(),
+        race: race_config.clone(),
+        settings: settings.clone(),
+    };
+
+    let Ok(text) = ron::ser::to_string_pretty(&save, ron::ser::PrettyConfig::default()) else {
+        return;
+    };
+
+    if pending.text != text {
+        pending.text = text;
+        pending.timer = Some(Timer::from_seconds(SAVE_DELAY, TimerMode::Once));
+    }
+}
+
+fn save_game_system(
+    storage: Res<SaveStorage>,
+    mut pending: ResMut<PendingSave>,
+    mut exit: EventReader<AppExit>,
+    time: Res<Time>,
+) {
+    let exiting = exit.read().count() > 0;
+    let Some(timer) = pending.timer.as_mut() else {
+        return;
+    };
+    if !timer.tick(time.delta()).finished() && !exiting {
+        return;
+    }
+
+    pending.timer = None;
+    storage.0.save(SAVE_KEY, &pending.text);
+}
+
+fn apply_settings_system(
+    settings: Res<Settings>,
+    mut volume: ResMut<GlobalVolume>,
+    mut egui_settings: ResMut<EguiSettings>,
+) {
+    volume.volume = Volume::new(settings.volume);
+    egui_settings.scale_factor = settings.ui_scale;
+}
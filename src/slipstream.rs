@@ -0,0 +1,117 @@
+use crate::bike::{bike_controller_system, Bicycle};
+use crate::elimination::Eliminated;
+use crate::game_state::{race_running, GameState};
+use avian2d::prelude::LinearVelocity;
+use bevy::prelude::*;
+
+/// How far behind another bike the slipstream reaches
+const DRAFT_RANGE: f32 = 6.0;
+/// Half the opening angle of the cone behind a bike, in degrees
+const DRAFT_CONE: f32 = 15.0;
+/// Too slow to get anything out of the slipstream
+const DRAFT_MIN_SPEED: f32 = 2.0;
+/// Seconds in the slipstream before the bonus starts to build
+const DRAFT_DELAY: f32 = 0.5;
+/// Seconds from the start of the bonus until it is at its maximum
+const DRAFT_BUILD_TIME: f32 = 2.0;
+/// Top speed bonus at the end of a long draft
+const MAX_DRAFT_BONUS: f32 = 0.15;
+const SPEED_LINES: usize = 3;
+
+pub struct SlipstreamPlugin;
+
+impl Plugin for SlipstreamPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                slipstream_system
+                    .before(bike_controller_system)
+                    .run_if(race_running),
+                speed_lines_system,
+            )
+                .run_if(in_state(GameState::Race)),
+        );
+    }
+}
+
+#[derive(Component, Debug, Default)]
+pub struct Slipstream {
+    /// Seconds spent in someone's slipstream without pulling out
+    time: f32,
+    /// Top speed multiplier on top of 1, applied in [`bike_controller_system`]
+    pub bonus: f32,
+}
+
+/// Whether a bike at `position` heading along `forward` sits in the cone behind the bike at
+/// `leader` and drives the same way
+fn in_slipstream(position: Vec2, forward: Vec2, leader: Vec2, leader_forward: Vec2) -> bool {
+    let offset = position - leader;
+    offset.length() < DRAFT_RANGE
+        && offset.angle_between(-leader_forward).abs() < DRAFT_CONE.to_radians()
+        && leader_forward.dot(forward) > DRAFT_CONE.to_radians().cos()
+}
+
+/// Following closely behind another bike builds up a top speed bonus, which is gone as soon as
+/// the racer pulls out
+pub fn slipstream_system(
+    mut bike_query: Query<(Entity, &GlobalTransform, &LinearVelocity, &mut Slipstream)>,
+    leader_query: Query<
+        (Entity, &GlobalTransform, &LinearVelocity),
+        (With<Bicycle>, Without<Eliminated>),
+    >,
+    time: Res<Time>,
+) {
+    for (bike, transform, velocity, mut slipstream) in bike_query.iter_mut() {
+        let position = transform.translation().xy();
+        let forward = transform.up().xy();
+        // A stopped bike has no slipstream to speak of
+        let drafting = velocity.length() > DRAFT_MIN_SPEED
+            && leader_query
+                .iter()
+                .any(|(leader, leader_transform, leader_velocity)| {
+                    leader != bike
+                        && leader_velocity.length() > DRAFT_MIN_SPEED
+                        && in_slipstream(
+                            position,
+                            forward,
+                            leader_transform.translation().xy(),
+                            leader_transform.up().xy(),
+                        )
+                });
+
+        if drafting {
+            slipstream.time += time.delta_seconds();
+        } else {
+            slipstream.time = 0.0;
+        }
+        slipstream.bonus = ((slipstream.time - DRAFT_DELAY) / DRAFT_BUILD_TIME).clamp(0.0, 1.0)
+            * MAX_DRAFT_BONUS;
+    }
+}
+
+/// Faint lines streaming past a bike in the slipstream, more visible the bigger the bonus
+pub fn speed_lines_system(
+    mut gizmos: Gizmos,
+    bike_query: Query<(&GlobalTransform, &Slipstream)>,
+    time: Res<Time>,
+) {
+    for (transform, slipstream) in bike_query.iter() {
+        if slipstream.bonus <= 0.0 {
+            continue;
+        }
+
+        let position = transform.translation().xy();
+        let forward = transform.up().xy();
+        let right = transform.right().xy();
+        let color = Color::WHITE.with_alpha(slipstream.bonus / MAX_DRAFT_BONUS * 0.5);
+
+        for i in 0..SPEED_LINES {
+            let side = i as f32 - (SPEED_LINES - 1) as f32 / 2.0;
+            // Staggered so the lines don't all move in lockstep
+            let phase = (time.elapsed_seconds() * 3.0 + i as f32 / SPEED_LINES as f32).fract();
+            let start = position + right * side * 0.6 + forward * (1.0 - phase * 2.5);
+            gizmos.line_2d(start, start - forward * 0.8, color);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Somewhere to keep small text blobs between sessions
+pub trait Storage: Send + Sync {
+    fn load(&self, key: &str) -> Option<String>;
+    fn save(&self, key: &str, value: &str);
+}
+
+/// Keeps each key as a file in the `saves` folder next to where the game is started from
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileStorage {
+    dir: std::path::PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileStorage {
+    pub fn new(dir: impl Into<std::path::PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, key: &str) -> std::path::PathBuf {
+        self.dir.join(format!("{}.ron", key))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Storage for FileStorage {
+    fn load(&self, key: &str) -> Option<String> {
+        std::fs::read_to_string(self.path(key)).ok()
+    }
+
+    fn save(&self, key: &str, value: &str) {
+        let result = std::fs::create_dir_all(&self.dir)
+            .and_then(|_| std::fs::write(self.path(key), value));
+        if let Err(err) = result {
+            warn!("Failed to save {}: {}", key, err);
+        }
+    }
+}
+
+/// Keeps each key in the browser's local storage
+#[cfg(target_arch = "wasm32")]
+pub struct LocalStorage;
+
+#[cfg(target_arch = "wasm32")]
+impl LocalStorage {
+    fn storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Storage for LocalStorage {
+    fn load(&self, key: &str) -> Option<String> {
+        Self::storage()?.get_item(key).ok()?
+    }
+
+    fn save(&self, key: &str, value: &str) {
+        if let Some(storage) = Self::storage() {
+            if storage.set_item(key, value).is_err() {
+                warn!("Failed to save {}", key);
+            }
+        }
+    }
+}
+
+/// The storage backend for the platform the game runs on
+#[derive(Resource)]
+pub struct SaveStorage(pub Box<dyn Storage>);
+
+impl Default for SaveStorage {
+    fn default() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        return Self(Box::new(FileStorage::new("saves")));
+        #[cfg(target_arch = "wasm32")]
+        return Self(Box::new(LocalStorage));
+    }
+}
+
+impl SaveStorage {
+    /// Loads and parses the value for `key`, `None` if there is none or it can't be read
+    pub fn load<T: for<'de> Deserialize<'de>>(&self, key: &str) -> Option<T> {
+        let text = self.0.load(key)?;
+        ron::from_str(&text)
+            .map_err(|err| warn!("Failed to read {}: {}", key, err))
+            .ok()
+    }
+
+    pub fn save<T: Serialize>(&self, key: &str, value: &T) {
+        match ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()) {
+            Ok(text) => self.0.save(key, &text),
+            Err(err) => warn!("Failed to write {}: {}", key, err),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::bike_config::{BicycleMod, ForBicycle};
+use crate::cup::{points_for, CupSlot};
+use crate::game_state::{GameState, RaceConfig};
+use bevy::color::Mix;
+use bevy::prelude::*;
+
+pub const MAX_TEAMS: usize = 4;
+pub const TEAMS: [(&str, Color); MAX_TEAMS] = [
+    ("Red", Color::srgb(0.9, 0.25, 0.2)),
+    ("Blue", Color::srgb(0.25, 0.45, 0.95)),
+    ("Green", Color::srgb(0.25, 0.75, 0.3)),
+    ("Yellow", Color::srgb(0.95, 0.8, 0.2)),
+];
+/// How strongly the bike frames are tinted in the team color, fully tinted frames lose their
+/// detail
+const FRAME_TINT: f32 = 0.5;
+
+pub struct TeamPlugin;
+
+impl Plugin for TeamPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, tint_team_frames.run_if(in_state(GameState::Race)));
+    }
+}
+
+/// The team a racer belongs to, racers are split into teams by their slot
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Team(pub usize);
+
+impl Team {
+    pub fn name(&self) -> &'static str {
+        TEAMS[self.0].0
+    }
+
+    pub fn color(&self) -> Color {
+        TEAMS[self.0].1
+    }
+
+    /// Items never go for teammates, racers without a team have none
+    pub fn same(a: Option<&Team>, b: Option<&Team>) -> bool {
+        matches!((a, b), (Some(a), Some(b)) if a == b)
+    }
+}
+
+/// Sums up the points every team's racers earned in a race, best team first
+pub fn team_points(race_config: &RaceConfig, results: &[(CupSlot, usize)]) -> Vec<(Team, usize)> {
+    let mut points: Vec<(Team, usize)> =
+        (0..race_config.teams.min(MAX_TEAMS)).map(|team| (Team(team), 0)).collect();
+    for (slot, rank) in results {
+        if let Some(team) = race_config.team_of(*slot) {
+            points[team.0].1 += points_for(*rank);
+        }
+    }
+    points.sort_by_key(|(_, points)| std::cmp::Reverse(*points));
+    points
+}
+
+pub fn tint_team_frames(
+    mut sprite_query: Query<(&mut Sprite, &BicycleMod, &ForBicycle), Added<ForBicycle>>,
+    team_query: Query<&Team>,
+) {
+    for (mut sprite, bicycle_mod, for_bicycle) in sprite_query.iter_mut() {
+        if !matches!(bicycle_mod, BicycleMod::Frame) {
+            continue;
+        }
+        if let Ok(team) = team_query.get(for_bicycle.0) {
+            sprite.color = Color::WHITE.mix(&team.color(), FRAME_TINT);
+        }
+    }
+}
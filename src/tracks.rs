@@ -0,0 +1,154 @@
+use crate::game_state::{MAPS, MAP_DATA};
+use bevy::prelude::*;
+use std::borrow::Cow;
+use std::ops::Deref;
+use svg::node::element::tag;
+use svg::parser::Event;
+
+/// Folder in `assets` that holds user track packs, one sub folder per pack
+#[cfg(not(target_arch = "wasm32"))]
+const USER_PACKS_DIR: &str = "tracks";
+
+#[derive(Debug, Clone)]
+pub struct Track {
+    pub name: String,
+    pub svg: Cow<'static, str>,
+    /// Asset folder that images linked in the svg are loaded from
+    pub asset_dir: String,
+    pub music: String,
+    /// Which user pack the track comes from, `None` for the built in tracks
+    pub pack: Option<String>,
+    /// Arena maps have no `track` polygon and are only used for battles
+    pub arena: bool,
+}
+
+/// What the track's svg says about itself: the music set with `data-music` on the root tag
+/// and whether it has a `track` polygon
+fn inspect(svg: &str) -> (Option<String>, bool) {
+    let Ok(events) = svg::read(svg) else {
+        return (None, false);
+    };
+
+    let mut music = None;
+    let mut has_track = false;
+    for event in events {
+        match event {
+            Event::Tag(tag::SVG, _, attrs) => {
+                music = attrs.get("data-music").map(|music| music.to_string());
+            }
+            Event::Tag(tag::Polygon, _, attrs) => {
+                has_track |= attrs.get("id").map(Deref::deref) == Some("track");
+            }
+            _ => {}
+        }
+    }
+    (music, has_track)
+}
+
+/// Every track that can be raced on: the built in ones and those of installed user packs
+#[derive(Resource, Debug)]
+pub struct TrackRegistry {
+    pub tracks: Vec<Track>,
+}
+
+impl Default for TrackRegistry {
+    fn default() -> Self {
+        let mut tracks: Vec<Track> = MAPS
+            .iter()
+            .zip(MAP_DATA)
+            .map(|(name, svg)| {
+                let (music, has_track) = inspect(svg);
+                Track {
+                    name: name.to_string(),
+                    svg: Cow::Borrowed(svg),
+                    asset_dir: "maps".to_string(),
+                    music: format!("music/{}.mp3", music.as_deref().unwrap_or(name)),
+                    pack: None,
+                    arena: !has_track,
+                }
+            })
+            .collect();
+
+        // Tracks are only known by their name, in records, saves and cups alike
+        #[cfg(not(target_arch = "wasm32"))]
+        for track in load_user_packs() {
+            if tracks.iter().any(|other| other.name == track.name) {
+                warn!(
+                    "Skipping track {}/{}, there already is a track with that name",
+                    track.pack.as_deref().unwrap_or_default(),
+                    track.name
+                );
+                continue;
+            }
+            tracks.push(track);
+        }
+
+        Self { tracks }
+    }
+}
+
+impl TrackRegistry {
+    pub fn get(&self, name: &str) -> Option<&Track> {
+        self.tracks.iter().find(|track| track.name == name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn default_track(&self) -> &Track {
+        &self.tracks[0]
+    }
+
+    /// The tracks that can be raced with or without laps, depending on `arena`
+    pub fn of_kind(&self, arena: bool) -> impl Iterator<Item = &Track> {
+        self.tracks.iter().filter(move |track| track.arena == arena)
+    }
+}
+
+/// Reads `assets/tracks/<pack>/<track>.svg`. The svg's images and an optional `<track>.mp3`
+/// (or the one named by `data-music`) are loaded from the same folder.
+#[cfg(not(target_arch = "wasm32"))]
+fn load_user_packs() -> Vec<Track> {
+    let Ok(packs) = std::fs::read_dir(std::path::Path::new("assets").join(USER_PACKS_DIR)) else {
+        return Vec::new();
+    };
+
+    let mut tracks = Vec::new();
+    for pack in packs.flatten().filter(|entry| entry.path().is_dir()) {
+        let pack_name = pack.file_name().to_string_lossy().to_string();
+        let Ok(files) = std::fs::read_dir(pack.path()) else {
+            continue;
+        };
+
+        for file in files.flatten() {
+            let path = file.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("svg") {
+                continue;
+            }
+            let Some(name) = path.file_stem().map(|stem| stem.to_string_lossy().to_string())
+            else {
+                continue;
+            };
+
+            match std::fs::read_to_string(&path) {
+                Ok(svg) => {
+                    let asset_dir = format!("{}/{}", USER_PACKS_DIR, pack_name);
+                    let (music, has_track) = inspect(&svg);
+                    tracks.push(Track {
+                        music: format!("{}/{}.mp3", asset_dir, music.as_deref().unwrap_or(&name)),
+                        name,
+                        svg: Cow::Owned(svg),
+                        asset_dir,
+                        pack: Some(pack_name.clone()),
+                        arena: !has_track,
+                    });
+                }
+                Err(err) => warn!("Failed to read track {:?}: {}", path, err),
+            }
+        }
+    }
+
+    info!("Loaded {} tracks from user packs", tracks.len());
+    tracks
+}
@@ -1,17 +1,67 @@
 use crate::addons::giraffe::PooCollision;
-use crate::bike::{BicycleControl, Player};
-use crate::ranking::{Progress, NEXT_CHECKPOINT_DISTANCE};
+use crate::ai::avoidance::AiAvoidance;
+use crate::ai::blocking::AiBlocking;
+use crate::ai::racing_line::RacingLine;
+use crate::ai::skill::AiSkill;
+use crate::bike::{BicycleControl, BicycleParams, DriftTurbo, Player};
+use crate::ranking::Progress;
 use avian2d::prelude::LinearVelocity;
 use bevy::prelude::*;
+use rand::random;
 
 #[derive(Component, Debug, Reflect)]
 pub struct Waypoint {
     pub next: Option<Entity>,
     pub index: usize,
+    /// Distance along the track from the start line to this waypoint
+    pub lap_distance: f32,
 }
 
+/// Length of one lap, following the waypoints
+#[derive(Resource, Debug, Default)]
+pub struct TrackLength(pub f32);
+
 #[derive(Component, Debug)]
-pub struct WaypointAi;
+pub struct WaypointAi {
+    /// The steering is only re-evaluated when this finishes, which simulates the bots reaction time.
+    /// Bots without a reaction delay have none and steer every frame.
+    reaction: Option<Timer>,
+    turn: f32,
+    acceleration: f32,
+    /// While this is running the bot messed up and steers into `mistake_turn`
+    mistake: Timer,
+    mistake_turn: f32,
+    /// Whether the bot is in a corner, it decides whether to drift it when it enters one
+    cornering: bool,
+    drift: bool,
+}
+
+impl WaypointAi {
+    pub fn new(skill: &AiSkill) -> Self {
+        let mut mistake = Timer::from_seconds(0.6, TimerMode::Once);
+        mistake.tick(mistake.duration());
+        Self {
+            reaction: (skill.reaction_delay > 0.0)
+                .then(|| Timer::from_seconds(skill.reaction_delay, TimerMode::Repeating)),
+            turn: 0.0,
+            acceleration: 1.0,
+            mistake,
+            mistake_turn: 0.0,
+            cornering: false,
+            drift: false,
+        }
+    }
+}
+
+/// Base distance the bots look ahead on the racing line to find their steering target
+const LOOKAHEAD_DISTANCE: f32 = 2.0;
+/// Additional lookahead per unit of speed
+const LOOKAHEAD_TIME: f32 = 0.4;
+/// How many seconds ahead the bots check the racing line for corners to brake for
+const BRAKING_TIME: f32 = 0.8;
+/// Bots consider themselves in a corner worth drifting above this turn input and speed
+const DRIFT_TURN: f32 = 0.5;
+const DRIFT_SPEED: f32 = 3.0;
 
 pub fn follow_waypoint(
     waypoint_query: Query<(&Transform, &Waypoint), Without<WaypointAi>>,
@@ -20,63 +70,114 @@ pub fn follow_waypoint(
             &mut Transform,
             &LinearVelocity,
             &mut BicycleControl,
-            &WaypointAi,
+            &mut WaypointAi,
+            &AiSkill,
+            &AiAvoidance,
+            &AiBlocking,
+            &BicycleParams,
             &Progress,
+            Option<&mut DriftTurbo>,
         ),
         (Without<Player>, Without<PooCollision>),
     >,
+    racing_line: Res<RacingLine>,
+    time: Res<Time>,
 ) {
-    for (transform, velocity, mut control, mut ai, progress) in bike_query.iter_mut() {
-        let (target_transform, target_waypoint) =
-            waypoint_query.get(progress.next_checkpoint).unwrap();
-        let (next_transform, next_waypoint) =
-            waypoint_query.get(target_waypoint.next.unwrap()).unwrap();
-
-        let target_position = target_transform.translation;
-
-        let direction = target_position - transform.translation;
-
-        let distance = direction.length();
-
-        let direction = direction.normalize().xy();
-        let forward = transform.up().xy();
-        let angle_deg = -direction.angle_between(forward).to_degrees();
+    if racing_line.is_empty() {
+        return;
+    }
 
-        let direction_next_waypoint = next_transform.translation - target_position;
-        let distance_next_waypoint = direction_next_waypoint.length();
-        let angle_next_waypoint_deg = -direction
-            .angle_between(direction_next_waypoint.normalize().xy())
-            .to_degrees();
+    for (
+        transform,
+        velocity,
+        mut control,
+        mut ai,
+        skill,
+        avoidance,
+        blocking,
+        params,
+        progress,
+        turbo,
+    ) in bike_query.iter_mut()
+    {
+        if !ai.mistake.tick(time.delta()).finished() {
+            control.turn = ai.mistake_turn;
+            control.acceleration = 0.6;
+            control.drift = false;
+            if let Some(mut turbo) = turbo {
+                turbo.cancel();
+            }
+            continue;
+        }
+        if random::<f32>() < skill.mistake_probability * time.delta_seconds() {
+            ai.mistake.reset();
+            ai.mistake_turn = if random() { 0.8 } else { -0.8 };
+        }
 
-        const CLOSING_IN_DIST: f32 = 10.0;
-        // closing_in is 0 if distance > 10 and 1 if distance < 3
-        let closing_in = 1.0 - (distance - 5.0).max(0.0).min(CLOSING_IN_DIST) / CLOSING_IN_DIST;
+        let reacting = ai
+            .reaction
+            .as_mut()
+            .map_or(true, |reaction| reaction.tick(time.delta()).just_finished());
+        if !reacting {
+            control.turn = ai.turn;
+            control.acceleration = ai.acceleration;
+            control.drift = ai.drift;
+            continue;
+        }
 
-        let turn = (angle_deg / 45.0).clamp(-1.0, 1.0);
-        let turn_next = (angle_next_waypoint_deg / 45.0).clamp(-1.0, 1.0);
+        let Ok((_, target_waypoint)) = waypoint_query.get(progress.next_checkpoint) else {
+            continue;
+        };
 
+        let position = transform.translation.xy();
         let current_speed = velocity.length();
 
-        let target_speed_at_waypoint = distance_next_waypoint * 2.0;
-
-        let accel_next = (target_speed_at_waypoint - current_speed).clamp(-1.0, 1.0);
+        let nearest = racing_line.nearest(position, target_waypoint.index);
+        // Look further ahead the faster we go so the line is followed smoothly
+        let lookahead =
+            (LOOKAHEAD_DISTANCE + current_speed * LOOKAHEAD_TIME) * skill.corner_cutting;
+        let forward = transform.up().xy();
+        let target_position = racing_line.points[racing_line.ahead(nearest, lookahead)]
+            + forward.perp() * blocking.offset;
 
-        let brake = turn_next * 0.3 * current_speed * 0.1;
+        let direction = (target_position - position).normalize_or_zero();
+        let angle_deg = -direction.angle_between(forward).to_degrees();
 
-        // let angle_diff = angle - transform.rotation.to_axis_angle().1;
-        control.turn = turn;
+        // Sloppy bots need a bigger angle before they fully commit to a turn
+        let full_turn_angle = 45.0 + (1.0 - skill.steering_precision) * 30.0;
+        let steering_noise = (random::<f32>() - 0.5) * (1.0 - skill.steering_precision) * 0.6;
 
-        let current_accel = 1.05 - (turn.abs() / 1.0);
-        let selected_accel_next = f32::min(1.05 - (turn_next.abs() / 1.0), accel_next);
+        let turn =
+            (angle_deg / full_turn_angle + steering_noise + avoidance.steer).clamp(-1.0, 1.0);
 
-        let mut accel = (selected_accel_next * closing_in) + (current_accel * (1.0 - closing_in));
+        // Brake early enough for whatever corner is coming up
+        let braking_distance = current_speed * BRAKING_TIME;
+        let target_speed = racing_line.speed_ahead(nearest, braking_distance)
+            * params.max_speed
+            * skill.cornering_speed
+            * avoidance.speed;
 
-        accel = f32::min(accel, current_accel);
+        let mut accel = ((target_speed - current_speed) * 2.0).clamp(-1.0, 1.0);
+        accel = f32::min(accel, 1.05 - turn.abs() * 0.5);
 
         if current_speed < 2.0 {
             accel = 1.0;
         }
 
+        // The drift is held through the whole corner and let go on the way out for the boost
+        let cornering = turn.abs() > DRIFT_TURN && current_speed > DRIFT_SPEED;
+        if cornering && !ai.cornering {
+            ai.drift = random::<f32>() < skill.drift_turbo;
+        } else if !cornering {
+            ai.drift = false;
+        }
+        ai.cornering = cornering;
+
+        control.turn = turn;
         control.acceleration = accel;
+        control.drift = ai.drift;
+
+        ai.turn = control.turn;
+        ai.acceleration = control.acceleration;
     }
 }